@@ -101,13 +101,13 @@ fn vtable2(attr: TokenStream, input: TokenStream) -> TokenStream {
     quote! {
         #[cfg(target_os = "windows")]
         #[repr(C)]
-        #[derive(Debug)]
+        #[derive(Debug, TestOffsets)]
         #windows1
         #[cfg(target_os = "windows")]
         #windows2
         #[cfg(target_os = "linux")]
         #[repr(C)]
-        #[derive(Debug)]
+        #[derive(Debug, TestOffsets)]
         #linux1
         #[cfg(target_os = "linux")]
         #linux2
@@ -128,9 +128,18 @@ fn derive_test_offset2(input: TokenStream) -> TokenStream {
         panic!()
     };
     let mut tests = TokenStream::new();
+    let mut fields = TokenStream::new();
     let ident = &input.ident;
     for field in x.fields {
         let field1 = field.ident.unwrap();
+        let ty = field.ty;
+        fields.extend(quote! {
+            crate::bindings::manifest::FieldLayout {
+                name: stringify!(#field1),
+                offset: std::mem::offset_of!(#ident, #field1),
+                size: std::mem::size_of::<#ty>(),
+            },
+        });
         for attr in field.attrs {
             if !attr.path().is_ident("test_offset") {
                 continue;
@@ -144,6 +153,22 @@ fn derive_test_offset2(input: TokenStream) -> TokenStream {
             });
         }
     }
+    // generic structs are covered by the structs that use them
+    let register = if input.generics.params.is_empty() {
+        quote! {
+            #[ctor::ctor]
+            fn register_layout() {
+                crate::bindings::manifest::register(crate::bindings::manifest::StructLayout {
+                    name: stringify!(#ident),
+                    size: std::mem::size_of::<#ident>(),
+                    align: std::mem::align_of::<#ident>(),
+                    fields: vec![#fields],
+                });
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
     let test_ident = Ident::new(
         &("__test_mod_".to_owned() + &input.ident.to_string()),
         Span::call_site(),
@@ -152,10 +177,12 @@ fn derive_test_offset2(input: TokenStream) -> TokenStream {
         #[cfg(test)]
         #[allow(non_snake_case)]
         mod #test_ident {
+            use super::*;
             #[test]
             fn test() {
                 #tests
             }
+            #register
         }
     }
 }
@@ -0,0 +1,159 @@
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+    process::ExitCode,
+};
+
+use neuro_ftl::{
+    pak::{Data, PakBuilder},
+    vfs::glob_match,
+};
+
+const USAGE: &str = "\
+Usage:
+    ftl-pak list <pak> [pattern...]
+    ftl-pak extract <pak> <dir> [pattern...]
+    ftl-pak pack [--compress] <dir> <pak>
+    ftl-pak diff <old pak> <new pak>
+
+Patterns are globs (`*`, `**`, `?`) matched against paths inside the pak, such as
+`data/*.xml`. If no pattern is given, all files are used. Both ftl.dat and the legacy
+data.dat/resource.dat format can be read.";
+
+fn matches(patterns: &[String], name: &str) -> bool {
+    patterns.is_empty() || patterns.iter().any(|x| glob_match(x, name))
+}
+
+fn list(pak: &str, patterns: &[String]) -> io::Result<()> {
+    let pak = Data::open(pak)?;
+    for name in pak.file_list() {
+        if matches(patterns, &name) {
+            println!("{name}");
+        }
+    }
+    Ok(())
+}
+
+fn extract(pak: &str, dir: &str, patterns: &[String]) -> io::Result<()> {
+    let mut pak = Data::open(pak)?;
+    let dir = Path::new(dir);
+    let mut found = false;
+    for name in pak.file_list() {
+        if !matches(patterns, &name) {
+            continue;
+        }
+        found = true;
+        let rel = Path::new(&name);
+        if !rel.components().all(|x| matches!(x, Component::Normal(_))) {
+            eprintln!("skipping {name}: unsafe path");
+            continue;
+        }
+        let path = dir.join(rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, pak.read(&name)?)?;
+        println!("{name}");
+    }
+    if !found {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no files matched the given patterns",
+        ));
+    }
+    Ok(())
+}
+
+fn pack(dir: &str, pak: &str, compress: bool) -> io::Result<()> {
+    let mut builder = PakBuilder::new();
+    let mut stack = vec![(PathBuf::from(dir), String::new())];
+    while let Some((dir, prefix)) = stack.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let Ok(name) = entry.file_name().into_string() else {
+                eprintln!("skipping {}: path is not UTF-8", entry.path().display());
+                continue;
+            };
+            let name = format!("{prefix}{name}");
+            if entry.file_type()?.is_dir() {
+                stack.push((entry.path(), format!("{name}/")));
+            } else if compress {
+                builder.add_compressed(&name, std::fs::read(entry.path())?);
+            } else {
+                builder.add(&name, std::fs::read(entry.path())?);
+            }
+        }
+    }
+    builder.save(pak)?;
+    println!("packed {} files", builder.file_list().len());
+    Ok(())
+}
+
+fn diff(old: &str, new: &str) -> io::Result<bool> {
+    let mut old = Data::open(old)?;
+    let mut new = Data::open(new)?;
+    let old_list = old.file_list();
+    let new_list = new.file_list();
+    let mut changed = false;
+    let (mut i, mut j) = (0, 0);
+    while i < old_list.len() || j < new_list.len() {
+        let (a, b) = (old_list.get(i), new_list.get(j));
+        match (a, b) {
+            (Some(a), Some(b)) if a == b => {
+                if old.read(a)? != new.read(b)? {
+                    println!("M {a}");
+                    changed = true;
+                }
+                i += 1;
+                j += 1;
+            }
+            (Some(a), b) if b.is_none_or(|b| a < b) => {
+                println!("- {a}");
+                changed = true;
+                i += 1;
+            }
+            (_, Some(b)) => {
+                println!("+ {b}");
+                changed = true;
+                j += 1;
+            }
+            _ => unreachable!(),
+        }
+    }
+    Ok(changed)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let ret = match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["list", pak, ..] => list(pak, &args[2..]),
+        ["extract", pak, dir, ..] => extract(pak, dir, &args[3..]),
+        ["pack", "--compress", dir, pak] => pack(dir, pak, true),
+        ["pack", dir, pak] => pack(dir, pak, false),
+        // like diff(1), exit with 1 if the paks differ
+        ["diff", old, new] => match diff(old, new) {
+            Ok(true) => return ExitCode::from(1),
+            x => x.map(|_| ()),
+        },
+        ["-h" | "--help" | "help"] => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match ret {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
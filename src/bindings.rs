@@ -1,6 +1,7 @@
 #![allow(clippy::too_many_arguments, clippy::type_complexity)]
 use std::{
     borrow::Cow,
+    cell::Cell,
     cmp::Ordering,
     collections::BTreeMap,
     ffi::{c_double, c_float, c_int, c_uint},
@@ -9,7 +10,7 @@ use std::{
     mem,
     ops::{Deref, DerefMut, Range},
     ptr,
-    sync::atomic::AtomicI32,
+    sync::atomic::{AtomicBool, AtomicI32},
 };
 
 use neuro_ftl_derive::{vtable, TestOffsets};
@@ -19,14 +20,48 @@ use crate::game::{
     strings::{self, text},
 };
 
+static CHECK_POINTERS: AtomicBool = AtomicBool::new(false);
+thread_local! {
+    static INVALID_POINTER: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Make [`xb`], [`xc`] and [`xm`] check that pointers point to readable memory, so a bad pointer
+/// is a `None` rather than a crash. This is slower, so it's opt-in.
+pub fn set_check_pointers(enabled: bool) {
+    CHECK_POINTERS.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// The pointer rejected by this thread's last check, if the last check rejected one. Any later
+/// check clears it, so a bad pointer that was handled as a `None` isn't blamed for an unrelated
+/// panic.
+pub fn take_invalid_pointer() -> Option<usize> {
+    Some(INVALID_POINTER.replace(0)).filter(|x| *x != 0)
+}
+
+fn readable<T>(x: *const T) -> bool {
+    x.is_aligned() && crate::cross::readable(x.cast(), mem::size_of::<T>())
+}
+
+fn valid<T>(x: *const T) -> bool {
+    if !CHECK_POINTERS.load(std::sync::atomic::Ordering::Relaxed) {
+        return !x.is_null();
+    }
+    let invalid = !x.is_null() && !readable(x);
+    if invalid {
+        log::error!("invalid pointer {x:?} to {}", std::any::type_name::<T>());
+    }
+    INVALID_POINTER.set(if invalid { x as usize } else { 0 });
+    !x.is_null() && !invalid
+}
+
 pub unsafe fn xb<'a, T>(x: *const T) -> Option<&'a T> {
-    (!x.is_null()).then(|| &*x)
+    valid(x).then(|| &*x)
 }
 pub unsafe fn xc<'a, T>(x: *mut T) -> Option<&'a T> {
-    (!x.is_null()).then(|| &*x)
+    valid(x).then(|| &*x)
 }
 pub unsafe fn xm<'a, T>(x: *mut T) -> Option<&'a mut T> {
-    (!x.is_null()).then(|| &mut *x)
+    valid(x).then(|| &mut *x)
 }
 
 // 0 1 5 13 2 3 4 6 7 8 9 10 11 12 14 15 - systems
@@ -127,6 +162,36 @@ impl System {
             x
         })
     }
+    /// The name shown in game, in the game's language
+    pub fn title(&self) -> &'static str {
+        self.title_in(super::library())
+    }
+    /// The blueprint's title from `lib`, or the English name if it's missing from the game data
+    pub(crate) fn title_in<'a>(&self, lib: &'a crate::TextLibrary) -> &'a str {
+        if let Some(title) = lib.blueprint_name(self.name()) {
+            return title;
+        }
+        match self {
+            Self::Shields => "Shields",
+            Self::Engines => "Engines",
+            Self::Oxygen => "Oxygen",
+            Self::Weapons => "Weapons",
+            Self::Drones => "Drone Control",
+            Self::Medbay => "Medbay",
+            Self::Pilot => "Piloting",
+            Self::Sensors => "Sensors",
+            Self::Doors => "Door System",
+            Self::Teleporter => "Teleporter",
+            Self::Cloaking => "Cloaking",
+            Self::Artillery => "Artillery",
+            Self::Battery => "Backup Battery",
+            Self::Clonebay => "Clone Bay",
+            Self::Mind => "Mind Control",
+            Self::Hacking => "Hacking",
+            Self::Reactor => "Reactor",
+            _ => self.name(),
+        }
+    }
     pub fn from_name(name: &str) -> Option<Self> {
         Some(match name {
             "shields" => Self::Shields,
@@ -368,7 +433,7 @@ pub struct VtableCEvent {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, TestOffsets)]
 pub struct CEvent {
     pub vtable: *const VtableCEvent,
 }
@@ -759,6 +824,7 @@ pub struct MainMenu {
 }
 
 #[repr(C)]
+#[derive(TestOffsets)]
 pub struct BossShip {
     pub base: CompleteShip,
     pub current_stage: c_int,
@@ -835,6 +901,9 @@ impl WorldManager {
     pub fn base_location_event_mut(&mut self) -> Option<&mut LocationEvent> {
         unsafe { xm(self.base_location_event) }
     }
+    pub fn last_location_event(&self) -> Option<&LocationEvent> {
+        unsafe { xc(self.last_location_event) }
+    }
 }
 
 #[repr(C)]
@@ -957,7 +1026,7 @@ pub enum TouchPauseReason {
 pub type GL_FrameBuffer = c_int;
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, TestOffsets)]
 #[allow(non_camel_case_types)]
 pub struct GL_Texture {
     pub id: c_int,
@@ -971,7 +1040,7 @@ pub struct GL_Texture {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, TestOffsets)]
 #[allow(non_camel_case_types)]
 pub struct GL_Color {
     pub r: c_float,
@@ -981,7 +1050,7 @@ pub struct GL_Color {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, TestOffsets)]
 #[allow(non_camel_case_types)]
 pub struct GL_Primitive {
     pub type_: c_int,
@@ -1055,14 +1124,14 @@ impl<T> Drop for Vector<T> {
 pub type VectorBoolStorage = usize;
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, TestOffsets)]
 pub struct VectorBoolIter {
     pub ptr: *mut VectorBoolStorage,
     pub offset: u32,
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, TestOffsets)]
 pub struct VectorBool {
     pub start: VectorBoolIter,
     pub finish: VectorBoolIter,
@@ -1110,14 +1179,14 @@ pub struct StdStringRep {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, TestOffsets)]
 #[cfg(target_os = "linux")]
 pub struct StdString {
     pub data: *const StdStringRep,
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, TestOffsets)]
 #[cfg(target_os = "windows")]
 pub struct StdString {
     // if it equals &res, stack allocation
@@ -5096,7 +5165,7 @@ pub struct VtableCollideable {
 }
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, TestOffsets)]
 pub struct Collideable {
     pub vtable: *const VtableCollideable,
 }
@@ -9073,7 +9142,7 @@ impl CommandGui {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, TestOffsets)]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -9092,7 +9161,7 @@ impl Point {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, TestOffsets)]
 pub struct Pointf {
     pub x: f32,
     pub y: f32,
@@ -9939,9 +10008,65 @@ pub const SHIP_BLUEPRINTS: [&str; 30] = [
 ];
 
 #[cfg(test)]
-mod test {
+mod manifest;
+
+#[cfg(test)]
+pub(crate) mod test {
     use crate::bindings::{VtableRepairable, VtableSelectable};
-    use std::mem;
+    use std::{
+        mem,
+        sync::{Mutex, MutexGuard},
+    };
+
+    static CHECK_POINTERS: Mutex<()> = Mutex::new(());
+
+    /// Turns pointer checks on until it's dropped. Tests that use it run one at a time, as the
+    /// setting is global.
+    pub(crate) struct CheckPointers(#[allow(dead_code)] MutexGuard<'static, ()>);
+
+    impl CheckPointers {
+        pub(crate) fn new() -> Self {
+            let lock = CHECK_POINTERS.lock().unwrap_or_else(|x| x.into_inner());
+            super::set_check_pointers(true);
+            Self(lock)
+        }
+    }
+
+    impl Drop for CheckPointers {
+        fn drop(&mut self) {
+            super::set_check_pointers(false);
+        }
+    }
+
+    #[test]
+    fn test_check_pointers() {
+        let mut x = 5u64;
+        assert!(super::readable(&raw const x));
+        assert!(!super::readable(0x1000 as *const u64));
+        assert!(!super::readable(unsafe { (&raw const x).byte_add(1) }));
+
+        let _checks = CheckPointers::new();
+        let bad = 0x1000 as *mut u64;
+        unsafe {
+            assert!(super::xb(bad.cast_const()).is_none());
+            assert!(super::xc(bad).is_none());
+            assert!(super::xm(bad).is_none());
+            assert_eq!(super::take_invalid_pointer(), Some(0x1000));
+            assert_eq!(super::take_invalid_pointer(), None);
+
+            // only the last check counts
+            assert!(super::xc(bad).is_none());
+            assert_eq!(super::xc(&raw mut x), Some(&5));
+            assert_eq!(super::take_invalid_pointer(), None);
+            assert!(super::xm(&raw mut x).is_some());
+            assert!(super::xb(0x2000 as *const u64).is_none());
+            assert_eq!(super::take_invalid_pointer(), Some(0x2000));
+
+            // null is a plain `None`, not an invalid pointer
+            assert!(super::xc(std::ptr::null_mut::<u64>()).is_none());
+            assert_eq!(super::take_invalid_pointer(), None);
+        }
+    }
 
     #[test]
     fn test() {
@@ -0,0 +1,88 @@
+//! Sizes and field offsets of every struct deriving `TestOffsets`, checked against a manifest
+//! per target so changing a struct can't silently shift the fields after it. After an intentional
+//! change, regenerate the manifest with `NEURO_FTL_BLESS_LAYOUT=1 cargo test manifest` on that
+//! target (for Windows, `--target i686-pc-windows-gnu`).
+use std::{fmt::Write, path::PathBuf, sync::Mutex};
+
+pub struct FieldLayout {
+    pub name: &'static str,
+    pub offset: usize,
+    pub size: usize,
+}
+
+pub struct StructLayout {
+    pub name: &'static str,
+    pub size: usize,
+    pub align: usize,
+    pub fields: Vec<FieldLayout>,
+}
+
+static LAYOUTS: Mutex<Vec<StructLayout>> = Mutex::new(Vec::new());
+
+/// Called by `TestOffsets` before the tests start
+pub fn register(layout: StructLayout) {
+    LAYOUTS.lock().unwrap().push(layout);
+}
+
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+const TARGET: &str = "x86_64-unknown-linux-gnu";
+#[cfg(all(target_os = "windows", target_pointer_width = "32"))]
+const TARGET: &str = "i686-pc-windows-gnu";
+
+fn manifest() -> String {
+    let mut layouts = LAYOUTS.lock().unwrap();
+    layouts.sort_by_key(|x| x.name);
+    let mut ret = format!("# {TARGET}: struct size align, then field offset size\n");
+    for layout in layouts.iter() {
+        writeln!(
+            ret,
+            "{} {:#x} {:#x}",
+            layout.name, layout.size, layout.align
+        )
+        .unwrap();
+        for field in &layout.fields {
+            writeln!(
+                ret,
+                "    {} {:#x} {:#x}",
+                field.name, field.offset, field.size
+            )
+            .unwrap();
+        }
+    }
+    ret
+}
+
+#[test]
+fn test_manifest() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/bindings")
+        .join(format!("layout-{TARGET}.txt"));
+    let actual = manifest();
+    if std::env::var_os("NEURO_FTL_BLESS_LAYOUT").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "failed to read {}: {err}, generate it with NEURO_FTL_BLESS_LAYOUT=1",
+            path.display()
+        )
+    });
+    // report the first struct that differs rather than the whole file
+    let mut name = "";
+    for (actual, expected) in actual.lines().zip(expected.lines()) {
+        if !actual.starts_with(' ') {
+            name = actual.split(' ').next().unwrap_or_default();
+        }
+        assert_eq!(
+            actual, expected,
+            "layout of {name} changed, if that's intended regenerate the manifest with \
+            NEURO_FTL_BLESS_LAYOUT=1"
+        );
+    }
+    assert_eq!(
+        actual.lines().count(),
+        expected.lines().count(),
+        "structs were added or removed, regenerate the manifest with NEURO_FTL_BLESS_LAYOUT=1"
+    );
+}
@@ -0,0 +1,274 @@
+//! Searching the game's item blueprints by their stats, e.g. "ion weapons under 60 scrap"
+use neuro_ftl_derive::JsonSchemaNoRef;
+use serde::{Deserialize, Serialize};
+
+use crate::{xml, Blueprint, TextLibrary};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchemaNoRef)]
+#[serde(rename_all = "snake_case")]
+pub enum BlueprintKind {
+    #[allow(unused)]
+    Weapon,
+    #[allow(unused)]
+    Drone,
+    #[allow(unused)]
+    Augment,
+    #[allow(unused)]
+    Crew,
+    #[allow(unused)]
+    System,
+    #[allow(unused)]
+    Item,
+    #[allow(unused)]
+    Ship,
+}
+
+/// Filters for [`TextLibrary::query_blueprints`], all of them have to match
+#[derive(Clone, Debug, Default)]
+pub struct BlueprintQuery {
+    pub kind: Option<BlueprintKind>,
+    pub min_cost: Option<u32>,
+    pub max_cost: Option<u32>,
+    /// hull damage per shot, only weapons have it
+    pub min_damage: Option<u32>,
+    /// lower is more common, items with rarity 0 never show up in stores
+    pub max_rarity: Option<u32>,
+    /// see [`BlueprintInfo::tags`]
+    pub tags: Vec<String>,
+    /// also include items that can't be bought
+    pub include_unavailable: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BlueprintInfo<'a> {
+    /// blueprint name
+    pub id: &'a str,
+    /// name shown in game
+    pub name: &'a str,
+    pub kind: BlueprintKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub damage: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rarity: Option<u32>,
+    /// weapon or drone type and special effects, e.g. "laser", "ion", "fire", "breach"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// The serialized name of a unit enum variant, e.g. `SHIP_REPAIR` -> `ship_repair`
+fn variant_name(x: &impl Serialize) -> String {
+    serde_json::to_value(x)
+        .ok()
+        .and_then(|x| x.as_str().map(str::to_lowercase))
+        .unwrap_or_default()
+}
+
+fn weapon_tags(b: &xml::WeaponBlueprint) -> Vec<String> {
+    let mut ret = vec![match b.type_ {
+        // burst weapons are flak, burst lasers are lasers
+        xml::WeaponType::Burst => "flak".to_owned(),
+        ref x => variant_name(x),
+    }];
+    let flags = [
+        ("ion", b.ion.is_some_and(|x| x > 0)),
+        ("fire", b.fire_chance > 0),
+        ("breach", b.breach_chance > 0),
+        (
+            "stun",
+            b.stun.is_some_and(|x| x > 0) || b.stun_chance.is_some_and(|x| x > 0),
+        ),
+        ("shield_piercing", b.sp.is_some_and(|x| x > 0)),
+        ("hull_buster", b.hull_bust == Some(true)),
+        ("crew_damage", b.pers_damage.is_some_and(|x| x > 0)),
+        ("system_damage", b.sys_damage.is_some_and(|x| x > 0)),
+        ("lockdown", b.lockdown == Some(true)),
+        ("uses_missiles", b.missiles.is_some_and(|x| x > 0)),
+    ];
+    ret.extend(flags.into_iter().filter(|x| x.1).map(|x| x.0.to_owned()));
+    ret
+}
+
+impl Blueprint {
+    fn info<'a>(&'a self, lib: &'a TextLibrary, id: &'a str) -> BlueprintInfo<'a> {
+        let mut ret = BlueprintInfo {
+            id,
+            name: lib.blueprint_name(id).unwrap_or(id),
+            kind: BlueprintKind::Ship,
+            cost: None,
+            damage: None,
+            power: None,
+            rarity: None,
+            tags: Vec::new(),
+        };
+        match self {
+            Self::Weapon(b) => {
+                ret.kind = BlueprintKind::Weapon;
+                ret.cost = b.cost;
+                ret.damage = Some(b.damage);
+                ret.power = b.power;
+                ret.rarity = Some(b.rarity);
+                ret.tags = weapon_tags(b);
+            }
+            Self::Drone(b) => {
+                ret.kind = BlueprintKind::Drone;
+                ret.cost = Some(b.cost);
+                ret.power = Some(b.power);
+                ret.rarity = Some(b.rarity);
+                ret.tags = vec![variant_name(&b.type_)];
+            }
+            Self::Augment(b) => {
+                ret.kind = BlueprintKind::Augment;
+                ret.cost = Some(b.cost);
+                ret.rarity = Some(b.rarity);
+                if b.stackable {
+                    ret.tags.push("stackable".to_owned());
+                }
+            }
+            Self::Crew(b) => {
+                ret.kind = BlueprintKind::Crew;
+                ret.cost = Some(b.cost);
+                ret.rarity = Some(b.rarity);
+            }
+            Self::System(b) => {
+                ret.kind = BlueprintKind::System;
+                ret.cost = Some(b.cost);
+                ret.power = Some(b.start_power);
+                ret.rarity = Some(b.rarity);
+                ret.tags = vec![variant_name(&b.type_)];
+            }
+            Self::Item(b) => {
+                ret.kind = BlueprintKind::Item;
+                ret.cost = Some(b.cost);
+                ret.rarity = Some(b.rarity);
+                ret.tags = vec![match b.type_ {
+                    xml::ItemType2::Drones => "drone_parts",
+                    xml::ItemType2::Missiles => "missiles",
+                    xml::ItemType2::Fuel => "fuel",
+                }
+                .to_owned()];
+            }
+            Self::Ship(_) => {}
+        }
+        ret
+    }
+}
+
+impl BlueprintQuery {
+    pub fn matches(&self, info: &BlueprintInfo) -> bool {
+        let at_least =
+            |x: Option<u32>, min: Option<u32>| min.is_none_or(|min| x.is_some_and(|x| x >= min));
+        let at_most =
+            |x: Option<u32>, max: Option<u32>| max.is_none_or(|max| x.is_some_and(|x| x <= max));
+        (self.include_unavailable || info.cost.is_some() && info.rarity.is_some_and(|x| x > 0))
+            && self.kind.is_none_or(|x| x == info.kind)
+            && at_least(info.cost, self.min_cost)
+            && at_most(info.cost, self.max_cost)
+            && at_least(info.damage, self.min_damage)
+            && at_most(info.rarity, self.max_rarity)
+            && self
+                .tags
+                .iter()
+                .all(|tag| info.tags.iter().any(|x| x.eq_ignore_ascii_case(tag)))
+    }
+}
+
+impl TextLibrary {
+    /// Blueprints matching the query, cheapest first
+    pub fn query_blueprints(&self, query: &BlueprintQuery) -> Vec<BlueprintInfo<'_>> {
+        let mut ret: Vec<_> = self
+            .blueprints
+            .iter()
+            .map(|(id, b)| b.info(self, id))
+            .filter(|x| query.matches(x))
+            .collect();
+        ret.sort_by(|a, b| a.cost.cmp(&b.cost).then_with(|| a.id.cmp(b.id)));
+        ret
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BlueprintKind, BlueprintQuery};
+    use crate::TextLibrary;
+
+    #[test]
+    fn test_query_blueprints() {
+        let weapon = |name: &str, type_: &str, damage: u32, ion: u32, cost: u32, rarity: u32| {
+            format!(
+                r#"
+                <weaponBlueprint name="{name}">
+                    <type>{type_}</type><title>{name}</title><damage>{damage}</damage>
+                    <ion>{ion}</ion><fireChance>0</fireChance><breachChance>0</breachChance>
+                    <cost>{cost}</cost><rarity>{rarity}</rarity><power>1</power>
+                    <launchSounds/><weaponArt>x</weaponArt>
+                </weaponBlueprint>"#
+            )
+        };
+        let text = format!(
+            r#"<FTL>{}{}{}{}
+            <augBlueprint name="AUG">
+                <title>Augment</title><desc>A</desc><cost>30</cost><rarity>1</rarity><stackable>false</stackable>
+            </augBlueprint>
+            </FTL>"#,
+            weapon("ION_1", "LASER", 0, 1, 30, 2),
+            weapon("ION_2", "BURST", 0, 2, 70, 3),
+            weapon("LASER_1", "LASER", 1, 0, 20, 1),
+            weapon("ION_BOSS", "BURST", 0, 4, 50, 0),
+        );
+        let mut lib = TextLibrary::default();
+        lib.add_blueprints(quick_xml::de::from_str(&text).unwrap());
+        let ids = |query: BlueprintQuery| {
+            lib.query_blueprints(&query)
+                .into_iter()
+                .map(|x| x.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(BlueprintQuery::default()),
+            ["LASER_1", "AUG", "ION_1", "ION_2"]
+        );
+        assert_eq!(
+            ids(BlueprintQuery {
+                kind: Some(BlueprintKind::Weapon),
+                max_cost: Some(60),
+                tags: vec!["ion".to_owned()],
+                ..Default::default()
+            }),
+            ["ION_1"]
+        );
+        assert_eq!(
+            ids(BlueprintQuery {
+                tags: vec!["Ion".to_owned()],
+                include_unavailable: true,
+                ..Default::default()
+            }),
+            ["ION_1", "ION_BOSS", "ION_2"]
+        );
+        assert_eq!(
+            ids(BlueprintQuery {
+                min_damage: Some(1),
+                ..Default::default()
+            }),
+            ["LASER_1"]
+        );
+        assert_eq!(
+            ids(BlueprintQuery {
+                max_rarity: Some(1),
+                ..Default::default()
+            }),
+            ["LASER_1", "AUG"]
+        );
+        let info = &lib.query_blueprints(&BlueprintQuery {
+            kind: Some(BlueprintKind::Weapon),
+            max_cost: Some(30),
+            min_cost: Some(30),
+            ..Default::default()
+        })[0];
+        assert_eq!(info.tags, ["laser", "ion"]);
+        assert_eq!(info.power, Some(1));
+    }
+}
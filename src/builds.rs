@@ -0,0 +1,196 @@
+//! Game builds (different patches or repacks), identified by a fingerprint of the executable's
+//! code. Only the build the offsets in the bindings were written for is listed in
+//! `builds/<os>.txt`, other builds can be added with `NEURO_FTL_BUILDS`. A table is a text file:
+//!
+//! ```text
+//! # comment
+//! build FTL 1.6.13
+//! fingerprint 5a3c20 0123456789abcdef
+//! 41c490 41d4a0
+//! ```
+//!
+//! `fingerprint` is the size and hash of the executable's code in hex, as logged on startup, and
+//! can be repeated. Every other line maps an address as written in the bindings to the address in
+//! this build; addresses that aren't listed stay the same. A build without fingerprints is never
+//! identified, the reference build is then used if the game's main loop is where the bindings
+//! expect it.
+use std::{collections::HashMap, ffi::c_void, io};
+
+use crate::cross;
+
+#[cfg(target_os = "windows")]
+const BUILTIN: &str = include_str!("builds/windows.txt");
+#[cfg(target_os = "linux")]
+const BUILTIN: &str = include_str!("builds/linux.txt");
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fingerprint {
+    pub size: u64,
+    pub hash: u64,
+}
+
+impl Fingerprint {
+    /// FNV-1a over 8 byte words, the code is a few megabytes and this runs before the game starts
+    pub fn of_bytes(data: &[u8]) -> Self {
+        let mut hash = 0xcbf29ce484222325u64;
+        for x in data.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..x.len()].copy_from_slice(x);
+            hash ^= u64::from_le_bytes(word);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        Self {
+            size: data.len() as u64,
+            hash,
+        }
+    }
+    /// Fingerprint of the code of the executable loaded at `base`
+    pub unsafe fn of_image(base: *mut c_void) -> Option<Self> {
+        let (start, len) = cross::code(base)?;
+        Some(Self::of_bytes(std::slice::from_raw_parts(
+            base.byte_add(start).cast::<u8>(),
+            len,
+        )))
+    }
+}
+
+impl std::fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:x} {:016x}", self.size, self.hash)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Build {
+    pub name: String,
+    pub fingerprints: Vec<Fingerprint>,
+    /// address in the bindings -> address in this build
+    pub offsets: HashMap<usize, usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BuildTable {
+    pub builds: Vec<Build>,
+}
+
+fn invalid(line: usize, msg: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {line}: {msg}"))
+}
+
+fn hex<T>(line: usize, x: Option<&str>, what: &str) -> io::Result<T>
+where
+    T: TryFrom<u64>,
+{
+    let x = x.ok_or_else(|| invalid(line, format!("expected {what}")))?;
+    u64::from_str_radix(x.trim_start_matches("0x"), 16)
+        .ok()
+        .and_then(|x| T::try_from(x).ok())
+        .ok_or_else(|| invalid(line, format!("invalid {what} {x:?}")))
+}
+
+impl BuildTable {
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut ret = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("build ") {
+                ret.builds.push(Build {
+                    name: name.trim().to_owned(),
+                    ..Default::default()
+                });
+                continue;
+            }
+            let build = ret
+                .builds
+                .last_mut()
+                .ok_or_else(|| invalid(line_no, "expected a build name first"))?;
+            let mut words = line.split_whitespace();
+            if let Some(fingerprint) = line.strip_prefix("fingerprint ") {
+                let mut words = fingerprint.split_whitespace();
+                build.fingerprints.push(Fingerprint {
+                    size: hex(line_no, words.next(), "size")?,
+                    hash: hex(line_no, words.next(), "hash")?,
+                });
+            } else {
+                let from = hex(line_no, words.next(), "address")?;
+                let to = hex(line_no, words.next(), "address")?;
+                build.offsets.insert(from, to);
+            }
+        }
+        Ok(ret)
+    }
+    /// The builtin table and the tables from the files in `NEURO_FTL_BUILDS`
+    pub fn load() -> Self {
+        let mut ret = Self::parse(BUILTIN).expect("invalid builtin build table");
+        for path in std::env::var_os("NEURO_FTL_BUILDS")
+            .map(|x| std::env::split_paths(&x).collect::<Vec<_>>())
+            .unwrap_or_default()
+        {
+            match std::fs::read_to_string(&path).and_then(|x| Self::parse(&x)) {
+                Ok(table) => ret.builds.extend(table.builds),
+                Err(err) => log::error!("failed to load build table {}: {err}", path.display()),
+            }
+        }
+        ret
+    }
+    pub fn identify(&self, fingerprint: Fingerprint) -> Option<&Build> {
+        self.builds
+            .iter()
+            .find(|x| x.fingerprints.contains(&fingerprint))
+    }
+    /// The build the offsets in the bindings were written for, the first one in the builtin table
+    pub fn reference(&self) -> Option<&Build> {
+        self.builds.first()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BuildTable, Fingerprint};
+
+    #[test]
+    fn test_builds() {
+        let fp = Fingerprint::of_bytes(b"FTL");
+        let text = format!(
+            "# builds\n\
+            build FTL (GOG)\n\
+            fingerprint 10 0000000000000001\n\
+            fingerprint {fp}\n\
+            41c490 41d4a0 # main loop\n\
+            \n\
+            build FTL (Humble)\n\
+            0x402aa0 0x402ab0\n"
+        );
+        let table = BuildTable::parse(&text).unwrap();
+        assert_eq!(table.builds.len(), 2);
+        let build = table.identify(fp).unwrap();
+        assert_eq!(build.name, "FTL (GOG)");
+        assert_eq!(build.offsets[&0x41c490], 0x41d4a0);
+        assert_eq!(table.builds[1].offsets[&0x402aa0], 0x402ab0);
+        assert!(table.identify(Fingerprint::of_bytes(b"FTL2")).is_none());
+        assert_eq!(fp.size, 3);
+
+        let builtin = BuildTable::load();
+        assert!(builtin.reference().unwrap().offsets.is_empty());
+        for (text, os) in [
+            (include_str!("builds/linux.txt"), "Linux"),
+            (include_str!("builds/windows.txt"), "Windows"),
+        ] {
+            let table = BuildTable::parse(text).unwrap();
+            let reference = table.reference().unwrap();
+            assert!(reference.name.ends_with(&format!("({os})")));
+            assert!(reference.offsets.is_empty());
+        }
+
+        let err = BuildTable::parse("41c490 41d4a0").unwrap_err();
+        assert_eq!(err.to_string(), "line 1: expected a build name first");
+        let err = BuildTable::parse("build a\nfingerprint 10").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: expected hash");
+        let err = BuildTable::parse("build a\n41c490 xyz").unwrap_err();
+        assert_eq!(err.to_string(), "line 2: invalid address \"xyz\"");
+    }
+}
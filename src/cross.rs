@@ -1,6 +1,10 @@
-use std::sync::OnceLock;
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Mutex, OnceLock},
+};
 
-use retour::GenericDetour;
+use retour::{Function, GenericDetour};
 
 #[macro_export]
 macro_rules! cross_fn {
@@ -30,6 +34,553 @@ macro_rules! cross_fn {
     };
 }
 
+/// A byte pattern that a function starts with, such as `55 53 48 89 ?? 48`, with `??` matching any
+/// byte. If the function isn't at its usual offset (because the game was patched or linked
+/// differently), the executable's code is searched for the pattern instead.
+#[derive(Copy, Clone, Debug)]
+pub struct Signature {
+    pub name: &'static str,
+    pub windows: &'static str,
+    pub linux: &'static str,
+    /// for data, where the data's address is in the matched code on Windows and Linux
+    pub operand: Option<(usize, usize)>,
+}
+
+impl Signature {
+    /// An empty pattern means there's no signature for that platform
+    pub const fn new(name: &'static str, windows: &'static str, linux: &'static str) -> Self {
+        Self {
+            name,
+            windows,
+            linux,
+            operand: None,
+        }
+    }
+    /// A signature for data rather than a function. The pattern matches code that uses the data,
+    /// with the data's 32-bit absolute address at `windows`/`linux` bytes into the match.
+    pub const fn with_operand(mut self, windows: usize, linux: usize) -> Self {
+        self.operand = Some((windows, linux));
+        self
+    }
+    fn pattern(&self) -> &'static str {
+        #[cfg(target_os = "windows")]
+        {
+            self.windows
+        }
+        #[cfg(target_os = "linux")]
+        {
+            self.linux
+        }
+    }
+    fn operand(&self) -> Option<usize> {
+        #[cfg(target_os = "windows")]
+        {
+            self.operand.map(|x| x.0)
+        }
+        #[cfg(target_os = "linux")]
+        {
+            self.operand.map(|x| x.1)
+        }
+    }
+}
+
+fn parse_pattern(pattern: &str) -> Option<Vec<Option<u8>>> {
+    pattern
+        .split_whitespace()
+        .map(|x| match x {
+            "?" | "??" => Some(None),
+            x => u8::from_str_radix(x, 16).ok().map(Some),
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|x| x.iter().any(Option::is_some))
+}
+
+fn matches_at(code: &[u8], pattern: &[Option<u8>]) -> bool {
+    code.len() >= pattern.len()
+        && code
+            .iter()
+            .zip(pattern)
+            .all(|(a, b)| b.is_none_or(|b| *a == b))
+}
+
+fn find(code: &[u8], pattern: &[Option<u8>]) -> Vec<usize> {
+    (0..code.len())
+        .filter(|i| matches_at(&code[*i..], pattern))
+        .collect()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// the signature matched at the usual offset
+    Offset,
+    /// the function was found somewhere else by its signature
+    Signature,
+    /// the signature didn't match anywhere, the usual offset is used
+    NotFound,
+    /// the signature matched in several places, the usual offset is used
+    Ambiguous,
+}
+
+#[derive(Clone, Debug)]
+pub struct Resolved {
+    pub name: &'static str,
+    /// offset from the executable's base address
+    pub offset: usize,
+    pub resolution: Resolution,
+}
+
+static REPORT: Mutex<Vec<Resolved>> = Mutex::new(Vec::new());
+
+/// How every binding with a signature was resolved
+pub fn report() -> Vec<Resolved> {
+    REPORT.lock().unwrap().clone()
+}
+
+/// Log which symbols had to be found by signature, or weren't found at all
+pub fn log_report() {
+    let report = REPORT.lock().unwrap();
+    let names = |res: Resolution| {
+        report
+            .iter()
+            .filter(|x| x.resolution == res)
+            .map(|x| x.name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let moved = names(Resolution::Signature);
+    if !moved.is_empty() {
+        log::warn!("found by signature instead of the usual offset: {moved}");
+    }
+    for res in [Resolution::NotFound, Resolution::Ambiguous] {
+        let names = names(res);
+        if !names.is_empty() {
+            log::error!("signature {res:?}, using the usual offset: {names}");
+        }
+    }
+}
+
+/// Where the executable is normally loaded, addresses in the bindings are relative to this
+#[cfg(target_os = "windows")]
+pub const IMAGE_BASE: usize = 0x400000;
+#[cfg(target_os = "linux")]
+pub const IMAGE_BASE: usize = 0;
+
+static OFFSETS: OnceLock<HashMap<usize, usize>> = OnceLock::new();
+
+/// Use different offsets for this build of the game, see [`crate::builds`]. The keys and values
+/// are addresses as written in the bindings. Has to be called before any `init`.
+pub fn set_offsets(offsets: &HashMap<usize, usize>) {
+    let offsets = offsets
+        .iter()
+        .filter_map(|(k, v)| Some((k.checked_sub(IMAGE_BASE)?, v.checked_sub(IMAGE_BASE)?)))
+        .collect();
+    if OFFSETS.set(offsets).is_err() {
+        log::error!("offsets were already set");
+    }
+}
+
+/// The offset in this build for an offset from the bindings
+pub fn relocate(offset: usize) -> usize {
+    OFFSETS
+        .get()
+        .and_then(|x| x.get(&offset))
+        .copied()
+        .unwrap_or(offset)
+}
+
+trait Detour: Sync {
+    unsafe fn set_enabled(&self, enabled: bool) -> retour::Result<()>;
+    fn is_enabled(&self) -> bool;
+}
+
+impl<T: Function> Detour for GenericDetour<T> {
+    unsafe fn set_enabled(&self, enabled: bool) -> retour::Result<()> {
+        if enabled {
+            self.enable()
+        } else {
+            self.disable()
+        }
+    }
+    fn is_enabled(&self) -> bool {
+        self.is_enabled()
+    }
+}
+
+struct HookEntry {
+    name: &'static str,
+    offset: usize,
+    error: Option<String>,
+    detour: Option<&'static dyn Detour>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct HookInfo {
+    pub name: &'static str,
+    /// offset from the executable's base address
+    pub offset: usize,
+    pub enabled: bool,
+    /// why the hook couldn't be installed
+    pub error: Option<String>,
+}
+
+static HOOKS: Mutex<Vec<HookEntry>> = Mutex::new(Vec::new());
+
+/// Whether a comma-separated list of hook names lists this hook
+fn listed(list: &str, name: &str) -> bool {
+    list.split(',').any(|x| x.trim() == name)
+}
+
+/// Whether `NEURO_FTL_DISABLE_HOOKS` lists this hook, so it's installed disabled
+fn disabled_by_env(name: &str) -> bool {
+    std::env::var("NEURO_FTL_DISABLE_HOOKS").is_ok_and(|x| listed(&x, name))
+}
+
+/// Hook `target` and add it to the registry. If that fails, the error is logged and recorded
+/// instead, and `slot` stays empty so the game keeps running without this hook.
+pub unsafe fn install<T: Function>(
+    slot: &'static OnceLock<GenericDetour<T>>,
+    name: &'static str,
+    base: *mut std::ffi::c_void,
+    offset: usize,
+    target: T,
+    hook: T,
+) {
+    if slot.get().is_some() {
+        return;
+    }
+    log::info!("hooking {name} ({offset:x}) at {:?}", base.byte_add(offset));
+    let enabled = !disabled_by_env(name);
+    let res = GenericDetour::new(target, hook)
+        .map_err(|err| format!("failed to hook {name} ({offset:x}): {err}"))
+        .and_then(|detour| {
+            if enabled {
+                detour
+                    .enable()
+                    .map_err(|err| format!("failed to enable hook {name} ({offset:x}): {err}"))?;
+            } else {
+                log::warn!("{name} is disabled by NEURO_FTL_DISABLE_HOOKS");
+            }
+            Ok(detour)
+        });
+    let mut entry = HookEntry {
+        name,
+        offset,
+        error: None,
+        detour: None,
+    };
+    match res {
+        Ok(detour) => entry.detour = Some(slot.get_or_init(|| detour)),
+        Err(err) => {
+            log::error!("{err}");
+            entry.error = Some(err);
+        }
+    }
+    HOOKS.lock().unwrap().push(entry);
+}
+
+/// All hooks that were installed or failed to install
+pub fn hooks() -> Vec<HookInfo> {
+    HOOKS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|x| HookInfo {
+            name: x.name,
+            offset: x.offset,
+            enabled: x.detour.is_some_and(|x| x.is_enabled()),
+            error: x.error.clone(),
+        })
+        .collect()
+}
+
+/// Enable or disable an installed hook. A disabled hook passes every call straight to the game.
+/// Only call this from the game's main thread, outside of any hooked function.
+pub unsafe fn set_hook_enabled(name: &str, enabled: bool) -> io::Result<()> {
+    let hooks = HOOKS.lock().unwrap();
+    let entry = hooks
+        .iter()
+        .find(|x| x.name == name)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no hook {name}")))?;
+    let detour = entry.detour.ok_or_else(|| {
+        io::Error::other(format!(
+            "{name} isn't installed: {}",
+            entry.error.as_deref().unwrap_or_default()
+        ))
+    })?;
+    detour
+        .set_enabled(enabled)
+        .map_err(|err| io::Error::other(format!("failed to toggle {name}: {err}")))?;
+    log::info!("{name} {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+/// Log every hook and its state
+pub fn log_hooks() {
+    for hook in hooks() {
+        match hook.error {
+            Some(err) => log::error!("hook {} ({:x}): {err}", hook.name, hook.offset),
+            None => log::info!(
+                "hook {} ({:x}): {}",
+                hook.name,
+                hook.offset,
+                if hook.enabled { "enabled" } else { "disabled" }
+            ),
+        }
+    }
+}
+
+/// The executable's code, as an offset from `base` and a length
+#[cfg(target_os = "linux")]
+unsafe fn code_range(base: *mut std::ffi::c_void) -> Option<(usize, usize)> {
+    let exe = std::env::current_exe().ok()?;
+    let maps = std::fs::read_to_string("/proc/self/maps").ok()?;
+    let mut code = maps.lines().filter_map(|line| {
+        // start-end perms offset dev inode path
+        let mut parts = line.split_whitespace();
+        let (start, end) = parts.next()?.split_once('-')?;
+        let perms = parts.next()?;
+        let path = parts.nth(3)?;
+        if !perms.contains('x') || std::path::Path::new(path) != exe {
+            return None;
+        }
+        Some((
+            usize::from_str_radix(start, 16).ok()?,
+            usize::from_str_radix(end, 16).ok()?,
+        ))
+    });
+    let (start, mut end) = code.next()?;
+    // hooking a function changes its page's protection, which splits the mapping
+    for (start, next_end) in code {
+        if start != end {
+            break;
+        }
+        end = next_end;
+    }
+    Some((start.checked_sub(base as usize)?, end - start))
+}
+
+/// The executable's code, as an offset from `base` and a length
+#[cfg(target_os = "windows")]
+unsafe fn code_range(base: *mut std::ffi::c_void) -> Option<(usize, usize)> {
+    const IMAGE_SCN_MEM_EXECUTE: u32 = 0x20000000;
+    let base = base.cast::<u8>();
+    let read_u16 = |offset: usize| base.add(offset).cast::<u16>().read_unaligned() as usize;
+    let read_u32 = |offset: usize| base.add(offset).cast::<u32>().read_unaligned();
+    if std::slice::from_raw_parts(base, 2) != b"MZ" {
+        return None;
+    }
+    let nt = read_u32(0x3c) as usize;
+    if std::slice::from_raw_parts(base.add(nt), 4) != b"PE\0\0" {
+        return None;
+    }
+    let sections = read_u16(nt + 6);
+    let first = nt + 24 + read_u16(nt + 20);
+    (0..sections).map(|i| first + i * 40).find_map(|section| {
+        (read_u32(section + 36) & IMAGE_SCN_MEM_EXECUTE != 0).then(|| {
+            (
+                read_u32(section + 12) as usize,
+                read_u32(section + 8) as usize,
+            )
+        })
+    })
+}
+
+/// Readable memory regions as `start..end`, sorted and with adjacent regions merged
+#[cfg(target_os = "linux")]
+fn readable_regions() -> Vec<(usize, usize)> {
+    let Ok(maps) = std::fs::read_to_string("/proc/self/maps") else {
+        return Vec::new();
+    };
+    let mut ret: Vec<(usize, usize)> = Vec::new();
+    for line in maps.lines() {
+        let mut parts = line.split_whitespace();
+        let (Some(range), Some(perms)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        let Some((start, end)) = range.split_once('-') else {
+            continue;
+        };
+        let (Ok(start), Ok(end)) = (
+            usize::from_str_radix(start, 16),
+            usize::from_str_radix(end, 16),
+        ) else {
+            continue;
+        };
+        if !perms.starts_with('r') {
+            continue;
+        }
+        match ret.last_mut() {
+            Some(last) if last.1 == start => last.1 = end,
+            _ => ret.push((start, end)),
+        }
+    }
+    ret
+}
+
+/// Whether `len` bytes at `ptr` are mapped and readable
+#[cfg(target_os = "linux")]
+pub fn readable(ptr: *const std::ffi::c_void, len: usize) -> bool {
+    // the maps are only reread when a pointer isn't in them, so a region that was unmapped since
+    // then is still considered readable
+    static REGIONS: Mutex<Vec<(usize, usize)>> = Mutex::new(Vec::new());
+    let start = ptr as usize;
+    let Some(end) = start.checked_add(len) else {
+        return false;
+    };
+    let contains = |regions: &[(usize, usize)]| {
+        let i = regions.partition_point(|x| x.1 <= start);
+        regions.get(i).is_some_and(|x| x.0 <= start && end <= x.1)
+    };
+    let mut regions = REGIONS.lock().unwrap();
+    if contains(&regions) {
+        return true;
+    }
+    *regions = readable_regions();
+    contains(&regions)
+}
+
+/// Whether `len` bytes at `ptr` are mapped and readable
+#[cfg(target_os = "windows")]
+pub fn readable(ptr: *const std::ffi::c_void, len: usize) -> bool {
+    #[repr(C)]
+    struct MemoryBasicInformation {
+        base_address: *mut std::ffi::c_void,
+        allocation_base: *mut std::ffi::c_void,
+        allocation_protect: u32,
+        region_size: usize,
+        state: u32,
+        protect: u32,
+        type_: u32,
+    }
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn VirtualQuery(
+            address: *const std::ffi::c_void,
+            buffer: *mut MemoryBasicInformation,
+            length: usize,
+        ) -> usize;
+    }
+    const MEM_COMMIT: u32 = 0x1000;
+    // PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY and their PAGE_EXECUTE_* versions
+    const READABLE: u32 = 0x02 | 0x04 | 0x08 | 0x20 | 0x40 | 0x80;
+    const PAGE_GUARD: u32 = 0x100;
+    let mut addr = ptr as usize;
+    let Some(end) = addr.checked_add(len) else {
+        return false;
+    };
+    while addr < end {
+        let mut info = std::mem::MaybeUninit::<MemoryBasicInformation>::uninit();
+        let info = unsafe {
+            if VirtualQuery(
+                addr as *const std::ffi::c_void,
+                info.as_mut_ptr(),
+                std::mem::size_of::<MemoryBasicInformation>(),
+            ) == 0
+            {
+                return false;
+            }
+            info.assume_init()
+        };
+        if info.state != MEM_COMMIT
+            || info.protect & READABLE == 0
+            || info.protect & PAGE_GUARD != 0
+        {
+            return false;
+        }
+        addr = info.base_address as usize + info.region_size;
+    }
+    true
+}
+
+/// The executable's code, as an offset from `base` and a length
+pub unsafe fn code(base: *mut std::ffi::c_void) -> Option<(usize, usize)> {
+    static CODE: OnceLock<Option<(usize, usize)>> = OnceLock::new();
+    *CODE.get_or_init(|| code_range(base))
+}
+
+/// Where a function or data with the given signature is, as an offset from `base`. `offset` is
+/// where it usually is, and is used if the signature can't be found.
+pub unsafe fn resolve(base: *mut std::ffi::c_void, offset: usize, sig: &Signature) -> Resolved {
+    let mut ret = Resolved {
+        name: sig.name,
+        offset,
+        resolution: Resolution::NotFound,
+    };
+    if sig.pattern().is_empty() {
+        ret.resolution = Resolution::Offset;
+        return ret;
+    }
+    match (parse_pattern(sig.pattern()), code(base)) {
+        (None, _) => log::error!("invalid signature for {}: {:?}", sig.name, sig.pattern()),
+        (_, None) => log::error!(
+            "couldn't find the executable's code to search for {}",
+            sig.name
+        ),
+        (Some(pattern), Some((start, len))) => {
+            let code = std::slice::from_raw_parts(base.byte_add(start).cast::<u8>(), len);
+            resolve_in(
+                code,
+                start,
+                base as usize,
+                &pattern,
+                sig.operand(),
+                &mut ret,
+            );
+        }
+    }
+    let mut report = REPORT.lock().unwrap();
+    if !report.iter().any(|x| x.name == ret.name) {
+        report.push(ret.clone());
+    }
+    ret
+}
+
+/// `code` is at `start` from `base`, the address of the executable
+fn resolve_in(
+    code: &[u8],
+    start: usize,
+    base: usize,
+    pattern: &[Option<u8>],
+    operand: Option<usize>,
+    ret: &mut Resolved,
+) {
+    let offset = ret.offset;
+    let mut found: Vec<usize> = match operand {
+        None => {
+            if offset >= start
+                && offset - start < code.len()
+                && matches_at(&code[offset - start..], pattern)
+            {
+                ret.resolution = Resolution::Offset;
+                return;
+            }
+            find(code, pattern).into_iter().map(|x| start + x).collect()
+        }
+        Some(at) => find(code, pattern)
+            .into_iter()
+            .filter_map(|x| {
+                let addr = code.get(x + at..x + at + 4)?;
+                (u32::from_le_bytes(addr.try_into().ok()?) as usize).checked_sub(base)
+            })
+            .collect(),
+    };
+    found.sort_unstable();
+    found.dedup();
+    match found[..] {
+        [] => {}
+        [x] if x == offset => ret.resolution = Resolution::Offset,
+        [x] => {
+            log::warn!(
+                "{} isn't at {offset:x}, found it by signature at {x:x}",
+                ret.name
+            );
+            ret.offset = x;
+            ret.resolution = Resolution::Signature;
+        }
+        _ => ret.resolution = Resolution::Ambiguous,
+    }
+}
+
 #[repr(transparent)]
 pub struct Ptr<const WINDOWS_OFFSET: usize, const LINUX_OFFSET: usize, T>(pub *mut T);
 
@@ -45,7 +596,13 @@ impl<const WINDOWS_OFFSET: usize, const LINUX_OFFSET: usize, T>
         Self(std::ptr::null_mut())
     }
     pub unsafe fn init(&mut self, base: *mut std::ffi::c_void) {
-        self.0 = base.byte_add(Self::OFFSET).cast();
+        self.0 = base.byte_add(relocate(Self::OFFSET)).cast();
+    }
+    /// Like `init`, but finds the data by its signature if it has moved
+    pub unsafe fn init_with(&mut self, base: *mut std::ffi::c_void, sig: &Signature) {
+        self.0 = base
+            .byte_add(resolve(base, relocate(Self::OFFSET), sig).offset)
+            .cast();
     }
 }
 
@@ -68,9 +625,16 @@ macro_rules! impl_fns {
                 pub const fn new() -> Self {
                     Self(None)
                 }
-                #[allow(clippy::missing_transmute_annotations)]
                 pub unsafe fn init(&mut self, base: *mut std::ffi::c_void) {
-                    self.0 = std::mem::transmute(base.byte_add(Self::OFFSET));
+                    self.init_at(base, relocate(Self::OFFSET));
+                }
+                /// Like `init`, but finds the function by its signature if it has moved
+                pub unsafe fn init_with(&mut self, base: *mut std::ffi::c_void, sig: &Signature) {
+                    self.init_at(base, resolve(base, relocate(Self::OFFSET), sig).offset);
+                }
+                #[allow(clippy::missing_transmute_annotations)]
+                unsafe fn init_at(&mut self, base: *mut std::ffi::c_void, offset: usize) {
+                    self.0 = std::mem::transmute(base.byte_add(offset));
                 }
                 #[allow(clippy::too_many_arguments)]
                 pub unsafe fn call(&self, $name0: $arg0, $($name: $arg),*) -> R {
@@ -108,23 +672,36 @@ macro_rules! impl_fns {
                 pub const fn new() -> Self {
                     Self(OnceLock::new())
                 }
-                #[allow(clippy::missing_transmute_annotations)]
                 pub unsafe fn init(
-                    &mut self,
+                    &'static self,
+                    base: *mut std::ffi::c_void,
+                    name: &'static str,
+                    #[cfg(target_os = "windows")] hook: unsafe extern "fastcall" fn($arg0, std::ffi::c_int, $($arg),*) -> R,
+                    #[cfg(target_os = "linux")] hook: unsafe extern "C" fn($arg0, $($arg),*) -> R,
+                ) {
+                    self.init_at(base, name, relocate(Self::OFFSET), hook);
+                }
+                /// Like `init`, but finds the function by its signature if it has moved
+                pub unsafe fn init_with(
+                    &'static self,
+                    base: *mut std::ffi::c_void,
+                    sig: &Signature,
+                    #[cfg(target_os = "windows")] hook: unsafe extern "fastcall" fn($arg0, std::ffi::c_int, $($arg),*) -> R,
+                    #[cfg(target_os = "linux")] hook: unsafe extern "C" fn($arg0, $($arg),*) -> R,
+                ) {
+                    self.init_at(base, sig.name, resolve(base, relocate(Self::OFFSET), sig).offset, hook);
+                }
+                unsafe fn init_at(
+                    &'static self,
                     base: *mut std::ffi::c_void,
+                    name: &'static str,
+                    offset: usize,
                     #[cfg(target_os = "windows")] hook: unsafe extern "fastcall" fn($arg0, std::ffi::c_int, $($arg),*) -> R,
                     #[cfg(target_os = "linux")] hook: unsafe extern "C" fn($arg0, $($arg),*) -> R,
                 ) {
-                    self.0.get_or_init(|| {
-                        let mut func = $x::<WINDOWS_OFFSET, LINUX_OFFSET, $arg0, $($arg,)* R>::new();
-                        log::info!("hooking {:x} at {:?}", Self::OFFSET, base.byte_add(Self::OFFSET));
-                        func.init(base);
-                        let detour = GenericDetour::new(func.0.unwrap(), hook)
-                            .map_err(|err| format!("failed to hook {:x}: {err}", Self::OFFSET))
-                            .unwrap();
-                        detour.enable().map_err(|err| format!("failed to enable hook {:x}: {err}", Self::OFFSET)).unwrap();
-                        detour
-                    });
+                    let mut func = $x::<WINDOWS_OFFSET, LINUX_OFFSET, $arg0, $($arg,)* R>::new();
+                    func.init_at(base, offset);
+                    install(&self.0, name, base, offset, func.0.unwrap(), hook);
                 }
                 #[allow(clippy::too_many_arguments)]
                 pub unsafe fn call(&self, $name0: $arg0, $($name: $arg),*) -> R {
@@ -164,9 +741,16 @@ impl<const WINDOWS_OFFSET: usize, const LINUX_OFFSET: usize, A, R>
     pub const fn new() -> Self {
         Self(None)
     }
-    #[allow(clippy::missing_transmute_annotations)]
     pub unsafe fn init(&mut self, base: *mut std::ffi::c_void) {
-        self.0 = std::mem::transmute(base.byte_add(Self::OFFSET));
+        self.init_at(base, relocate(Self::OFFSET));
+    }
+    /// Like `init`, but finds the function by its signature if it has moved
+    pub unsafe fn init_with(&mut self, base: *mut std::ffi::c_void, sig: &Signature) {
+        self.init_at(base, resolve(base, relocate(Self::OFFSET), sig).offset);
+    }
+    #[allow(clippy::missing_transmute_annotations)]
+    unsafe fn init_at(&mut self, base: *mut std::ffi::c_void, offset: usize) {
+        self.0 = std::mem::transmute(base.byte_add(offset));
     }
     #[allow(clippy::too_many_arguments)]
     pub unsafe fn call(&self, a: A) -> R {
@@ -197,30 +781,41 @@ impl<const WINDOWS_OFFSET: usize, const LINUX_OFFSET: usize, A: 'static, R: 'sta
     pub const fn new() -> Self {
         Self(OnceLock::new())
     }
-    #[allow(clippy::missing_transmute_annotations)]
     pub unsafe fn init(
-        &mut self,
+        &'static self,
         base: *mut std::ffi::c_void,
+        name: &'static str,
         #[cfg(target_os = "windows")] hook: unsafe extern "fastcall" fn(A) -> R,
         #[cfg(target_os = "linux")] hook: unsafe extern "C" fn(A) -> R,
     ) {
-        self.0.get_or_init(|| {
-            let mut func = Fn1::<WINDOWS_OFFSET, LINUX_OFFSET, A, R>::new();
-            log::info!(
-                "hooking {:x} at {:?}",
-                Self::OFFSET,
-                base.byte_add(Self::OFFSET)
-            );
-            func.init(base);
-            let detour = GenericDetour::new(func.0.unwrap(), hook)
-                .map_err(|err| format!("failed to hook {:x}: {err}", Self::OFFSET))
-                .unwrap();
-            detour
-                .enable()
-                .map_err(|err| format!("failed to enable hook {:x}: {err}", Self::OFFSET))
-                .unwrap();
-            detour
-        });
+        self.init_at(base, name, relocate(Self::OFFSET), hook);
+    }
+    /// Like `init`, but finds the function by its signature if it has moved
+    pub unsafe fn init_with(
+        &'static self,
+        base: *mut std::ffi::c_void,
+        sig: &Signature,
+        #[cfg(target_os = "windows")] hook: unsafe extern "fastcall" fn(A) -> R,
+        #[cfg(target_os = "linux")] hook: unsafe extern "C" fn(A) -> R,
+    ) {
+        self.init_at(
+            base,
+            sig.name,
+            resolve(base, relocate(Self::OFFSET), sig).offset,
+            hook,
+        );
+    }
+    unsafe fn init_at(
+        &'static self,
+        base: *mut std::ffi::c_void,
+        name: &'static str,
+        offset: usize,
+        #[cfg(target_os = "windows")] hook: unsafe extern "fastcall" fn(A) -> R,
+        #[cfg(target_os = "linux")] hook: unsafe extern "C" fn(A) -> R,
+    ) {
+        let mut func = Fn1::<WINDOWS_OFFSET, LINUX_OFFSET, A, R>::new();
+        func.init_at(base, offset);
+        install(&self.0, name, base, offset, func.0.unwrap(), hook);
     }
     #[allow(clippy::too_many_arguments)]
     pub unsafe fn call(&self, a: A) -> R {
@@ -239,3 +834,158 @@ impl_fns!(
     (Fn8, Hook8, a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H),
     (Fn9, Hook9, a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H, i: I),
 );
+
+#[cfg(test)]
+mod test {
+    use std::sync::OnceLock;
+
+    use retour::GenericDetour;
+
+    use super::{find, parse_pattern, resolve_in, Resolution, Resolved};
+
+    #[test]
+    fn test_patterns() {
+        assert_eq!(
+            parse_pattern("55 ?? 48 ?"),
+            Some(vec![Some(0x55), None, Some(0x48), None])
+        );
+        assert_eq!(parse_pattern("55 4G"), None);
+        assert_eq!(parse_pattern("?? ??"), None);
+        assert_eq!(parse_pattern(""), None);
+
+        let code = [0x90, 0x55, 0x53, 0x48, 0x90, 0x55, 0x54, 0x48, 0x55];
+        let pattern = parse_pattern("55 ?? 48").unwrap();
+        assert_eq!(find(&code, &pattern), [1, 5]);
+        assert_eq!(find(&code, &parse_pattern("55 53").unwrap()), [1]);
+
+        let resolve = |offset, pattern: &str| {
+            let mut ret = Resolved {
+                name: "test",
+                offset,
+                resolution: Resolution::NotFound,
+            };
+            // the code starts at offset 0x100 from the base
+            let pattern = parse_pattern(pattern).unwrap();
+            resolve_in(&code, 0x100, 0, &pattern, None, &mut ret);
+            (ret.offset, ret.resolution)
+        };
+        assert_eq!(resolve(0x105, "55 ?? 48"), (0x105, Resolution::Offset));
+        assert_eq!(resolve(0x100, "55 54"), (0x105, Resolution::Signature));
+        assert_eq!(resolve(0x100, "55 ?? 48"), (0x100, Resolution::Ambiguous));
+        assert_eq!(resolve(0x100, "12 34"), (0x100, Resolution::NotFound));
+        // out of the code's range
+        assert_eq!(resolve(0x10, "55 53"), (0x101, Resolution::Signature));
+        assert_eq!(resolve(0x200, "55 53"), (0x101, Resolution::Signature));
+
+        // data at 0x4855 from the base at 0x10000, its address is in the 4 bytes after `90`
+        let code = [0x90, 0x55, 0x48, 0x01, 0x00, 0x90];
+        let resolve = |offset, pattern: &str| {
+            let mut ret = Resolved {
+                name: "test",
+                offset,
+                resolution: Resolution::NotFound,
+            };
+            let pattern = parse_pattern(pattern).unwrap();
+            resolve_in(&code, 0x100, 0x10000, &pattern, Some(1), &mut ret);
+            (ret.offset, ret.resolution)
+        };
+        assert_eq!(resolve(0x4855, "90 ?? ?? 01"), (0x4855, Resolution::Offset));
+        assert_eq!(
+            resolve(0x4000, "90 ?? ?? 01"),
+            (0x4855, Resolution::Signature)
+        );
+        // the match at the end of the code has no room for an address
+        assert_eq!(resolve(0x4000, "90"), (0x4855, Resolution::Signature));
+        assert_eq!(resolve(0x4000, "12 34"), (0x4000, Resolution::NotFound));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_moved() {
+        use super::{Fn1, Signature};
+
+        #[inline(never)]
+        extern "C" fn moved(x: i32) -> i32 {
+            std::hint::black_box(x).wrapping_mul(0x5A17C0DE)
+        }
+        // the function's first bytes, including the call to `black_box` which makes them unique
+        let code = unsafe { std::slice::from_raw_parts(moved as *const u8, 24) };
+        let pattern: String = code.iter().map(|x| format!("{x:02X} ")).collect();
+        let sig = Signature::new("moved", "", pattern.leak());
+        // the bindings say it's at 0x10, which isn't where it is
+        let mut func = Fn1::<0, 0x10, i32, i32>::new();
+        unsafe { func.init_with(std::ptr::null_mut(), &sig) };
+        assert_eq!(unsafe { func.call(3) }, moved(3));
+        let resolved = super::report()
+            .into_iter()
+            .find(|x| x.name == "moved")
+            .unwrap();
+        assert_eq!(resolved.resolution, Resolution::Signature);
+        assert_eq!(resolved.offset, moved as *const () as usize);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_code_range() {
+        let (start, len) = unsafe { super::code_range(std::ptr::null_mut()) }.unwrap();
+        let this = test_code_range as *const () as usize;
+        assert!((start..start + len).contains(&this));
+    }
+
+    #[test]
+    fn test_readable() {
+        let x = [0u8; 16];
+        assert!(super::readable(x.as_ptr().cast(), x.len()));
+        assert!(super::readable((test_readable as *const ()).cast(), 1));
+        assert!(!super::readable(std::ptr::null(), 1));
+        assert!(!super::readable(usize::MAX as *const _, 2));
+    }
+
+    #[inline(never)]
+    extern "C" fn original(x: i32) -> i32 {
+        std::hint::black_box(x) + 1
+    }
+    extern "C" fn replacement(x: i32) -> i32 {
+        HOOK.get().unwrap().call(x) * 10
+    }
+    static HOOK: OnceLock<GenericDetour<extern "C" fn(i32) -> i32>> = OnceLock::new();
+
+    #[test]
+    fn test_hooks() {
+        let func = std::hint::black_box(original as extern "C" fn(i32) -> i32);
+        unsafe {
+            super::install(
+                &HOOK,
+                "original",
+                std::ptr::null_mut(),
+                0x10,
+                func,
+                replacement,
+            );
+        }
+        assert_eq!(func(1), 20);
+        let info = super::hooks()
+            .into_iter()
+            .find(|x| x.name == "original")
+            .unwrap();
+        assert!(info.enabled);
+        assert_eq!(info.offset, 0x10);
+        assert_eq!(info.error, None);
+
+        unsafe { super::set_hook_enabled("original", false) }.unwrap();
+        assert_eq!(func(1), 2);
+        let info = super::hooks()
+            .into_iter()
+            .find(|x| x.name == "original")
+            .unwrap();
+        assert!(!info.enabled);
+        unsafe { super::set_hook_enabled("original", true) }.unwrap();
+        assert_eq!(func(1), 20);
+
+        let err = unsafe { super::set_hook_enabled("missing", false) }.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+
+        assert!(super::listed("a, original", "original"));
+        assert!(!super::listed("originals", "original"));
+    }
+}
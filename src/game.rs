@@ -5,7 +5,7 @@ use std::{
     mem,
     ops::DerefMut,
     ptr,
-    sync::OnceLock,
+    sync::{Mutex, OnceLock},
     time::{Duration, Instant},
 };
 
@@ -23,11 +23,15 @@ use tokio::sync::mpsc;
 
 use crate::{
     bindings::{self, power_manager, xb, xc, xm, CApp, Door, Projectile, System},
+    blueprints::BlueprintQuery,
     xml::DroneType,
 };
 
 pub mod actions;
 mod context;
+mod crash;
+mod outcomes;
+mod sectors;
 pub mod strings;
 
 const TIP_TIMEOUT: Duration = Duration::from_millis(500);
@@ -41,6 +45,41 @@ fn meta<T: Action>() -> neuro_sama::schema::Action {
     }
 }
 
+/// Like [`meta`], but with optional fields shown as non-nullable, to make the schema simpler
+fn meta_no_null<T: Action>() -> neuro_sama::schema::Action {
+    let mut meta = meta::<T>();
+    let sch = meta.schema.as_object_mut().unwrap();
+    for prop in sch
+        .get_mut("properties")
+        .unwrap()
+        .as_object_mut()
+        .unwrap()
+        .values_mut()
+    {
+        let prop = prop.as_object_mut().unwrap();
+        let ty = prop.get_mut("type").unwrap();
+        let mut ty1 = serde_json::Value::Null;
+        std::mem::swap(&mut ty1, ty);
+        let mut remove_def = false;
+        *ty = match ty1 {
+            serde_json::Value::Array(x) => {
+                remove_def = true;
+                x.into_iter()
+                    .find(|x| x.as_str().unwrap() != "null")
+                    .unwrap()
+            }
+            x => x,
+        };
+        if remove_def {
+            prop.remove("default");
+        }
+        if let Some(serde_json::Value::Array(x)) = prop.get_mut("enum") {
+            x.retain(|x| !x.is_null());
+        }
+    }
+    meta
+}
+
 struct State {
     cooldown: Option<Instant>,
     tx: mpsc::Sender<tungstenite::Message>,
@@ -56,6 +95,11 @@ struct State {
     projectile_stack: Vec<*mut Projectile>,
     projectiles: HashMap<*mut Projectile, String>,
     shot_q: Vec<(i32, *mut Projectile, String)>,
+    /// ids of the achievements that were unlocked last frame, `None` until they're first checked
+    unlocked_achievements: Option<HashSet<String>>,
+    /// the last few actions, for crash reports
+    history: VecDeque<crash::ActionRecord>,
+    outcomes: outcomes::OutcomeCache,
 }
 
 unsafe impl Sync for State {}
@@ -149,13 +193,11 @@ fn resource_event_str(
     }
     if let Some(upgrade_id) = System::from_id(res.upgrade_id) {
         if ship_manager.system(upgrade_id).is_some() {
-            let bp = upgrade_id.blueprint().unwrap();
-            ret.push(format!("{} will be upgraded", bp.title.to_str()));
+            ret.push(format!("{} will be upgraded", upgrade_id.title()));
         }
     }
     if let Some(system_id) = System::from_id(res.system_id) {
-        let bp = system_id.blueprint().unwrap();
-        ret.push(format!("{} will be installed", bp.title.to_str()));
+        ret.push(format!("{} will be installed", system_id.title()));
     }
     let ret = ret.join(", ");
     if !ret.is_empty() {
@@ -248,11 +290,64 @@ impl neuro_sama::game::GameMut for State {
         Option<impl 'static + Into<Cow<'static, str>>>,
         Option<impl 'static + Into<Cow<'static, str>>>,
     > {
+        log::debug!("handling action: {action:?}");
+        let desc = format!("{action:?}");
+        // forget pointers rejected before this action
+        bindings::take_invalid_pointer();
+        // with pointer checks on, a bad pointer panics on an `unwrap` instead of crashing the
+        // game, so it can be reported as a failed action
+        let ret = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.run_action(action)
+        })) {
+            Ok(ret) => ret,
+            Err(err) => match bindings::take_invalid_pointer() {
+                Some(ptr) => {
+                    crash::clear_panic();
+                    Err(Some(Cow::from(format!(
+                        "the action failed because the game's memory at {ptr:#x} is invalid, the \
+                        game may be in an inconsistent state"
+                    ))))
+                }
+                None => std::panic::resume_unwind(err),
+            },
+        };
+        crash::record_action(
+            &mut self.history,
+            crash::ActionRecord {
+                action: desc,
+                success: ret.is_ok(),
+                message: match &ret {
+                    Ok(x) | Err(x) => x.as_ref().map(|x| x.to_string()),
+                },
+            },
+        );
+        if let Some(force) = &mut self.actions.force {
+            if ret.is_ok() {
+                self.actions.force = None;
+            } else if force.send_at.is_none() {
+                force.send_at = Some(Instant::now() + force.retry_timeout);
+            }
+        }
+        ret
+    }
+    fn reregister_actions(&mut self) {
+        if let Err(err) =
+            self.register_actions_raw(self.actions.actions.values().cloned().collect())
+        {
+            log::error!("error reregistering actions: {err}");
+        }
+    }
+}
+
+impl State {
+    fn run_action(
+        &mut self,
+        action: FtlActions,
+    ) -> Result<Option<Cow<'static, str>>, Option<Cow<'static, str>>> {
         let Some(app) = self.app_mut() else {
             return Err(Cow::from("CApp is null, game is broken").into());
         };
-        log::debug!("handling action: {action:?}");
-        let ret: Result<Option<Cow<'static, str>>, Option<Cow<'static, str>>> = match action {
+        match action {
             // only main menu
             FtlActions::SkipCredits(event) => {
                 if self.actions.valid(&event) {
@@ -818,15 +913,8 @@ impl neuro_sama::game::GameMut for State {
                             .unwrap()
                             .systems_mut()
                             .find(|x| {
-                                map.map(
-                                    System::from_id(x.i_system_type)
-                                        .unwrap()
-                                        .blueprint()
-                                        .unwrap()
-                                        .title
-                                        .to_str()
-                                        .into(),
-                                ) == system
+                                map.map(System::from_id(x.i_system_type).unwrap().title().into())
+                                    == system
                             })
                     });
                     if let Some(system) = system {
@@ -897,9 +985,8 @@ impl neuro_sama::game::GameMut for State {
                                 .unwrap()
                                 .systems()
                                 .filter_map(|x| {
-                                    System::from_id(x.i_system_type).map(|x| {
-                                        map.map(x.blueprint().unwrap().title.to_str().into())
-                                    })
+                                    System::from_id(x.i_system_type)
+                                        .map(|x| map.map(x.title().into()))
                                 })
                                 .collect::<Vec<_>>()
                         });
@@ -1299,15 +1386,8 @@ impl neuro_sama::game::GameMut for State {
                         let target = target.ship_manager_mut().unwrap();
                         let system = IdMap::with(|map| {
                             target.systems_mut().find(|x| {
-                                map.map(
-                                    System::from_id(x.i_system_type)
-                                        .unwrap()
-                                        .blueprint()
-                                        .unwrap()
-                                        .title
-                                        .to_str()
-                                        .into(),
-                                ) == event.system
+                                map.map(System::from_id(x.i_system_type).unwrap().title().into())
+                                    == event.system
                             })
                         });
                         if let Some(system) = system {
@@ -1365,10 +1445,7 @@ impl neuro_sama::game::GameMut for State {
                                         map.map(
                                             System::from_id(x.i_system_type)
                                                 .unwrap()
-                                                .blueprint()
-                                                .unwrap()
-                                                .title
-                                                .to_str()
+                                                .title()
                                                 .into(),
                                         )
                                     })
@@ -3149,11 +3226,16 @@ impl neuro_sama::game::GameMut for State {
                         event.what,
                         RememberType::Everything | RememberType::CurrentEvent
                     ) {
-                        let gui = app.gui().unwrap();
-                        let (event_text, event_options) = event_options(gui);
+                        let (event_text, event_options) = event_options(app, &self.outcomes);
                         ret.event_text = event_text;
                         ret.event_options = event_options;
                     }
+                    if matches!(
+                        event.what,
+                        RememberType::Everything | RememberType::Achievements
+                    ) {
+                        ret.achievements = achievements();
+                    }
                     match event.what {
                         RememberType::Inventory => {}
                         RememberType::Store => {}
@@ -3161,6 +3243,7 @@ impl neuro_sama::game::GameMut for State {
                         RememberType::StarMap => {}
                         RememberType::SectorMap => {}
                         RememberType::CurrentEvent => {}
+                        RememberType::Achievements => {}
                         RememberType::Everything => {}
                     }
                     let ret = Some(Cow::from(format!(
@@ -3177,6 +3260,70 @@ impl neuro_sama::game::GameMut for State {
                     }
                 }
             }
+            FtlActions::FindItems(event) => {
+                if !self.actions.valid(&event) {
+                    Err(Cow::from("can't search items at the moment").into())
+                } else {
+                    const MAX_ITEMS: usize = 30;
+                    let query = BlueprintQuery {
+                        kind: event.kind,
+                        min_cost: event.min_cost,
+                        max_cost: event.max_cost,
+                        min_damage: event.min_damage,
+                        max_rarity: event.max_rarity,
+                        tags: event.tags.unwrap_or_default(),
+                        include_unavailable: event.include_unavailable.unwrap_or_default(),
+                    };
+                    let items = crate::library().query_blueprints(&query);
+                    let mut ret = format!(
+                        "Items: {}",
+                        serde_json::to_string(&items[..items.len().min(MAX_ITEMS)]).unwrap()
+                    );
+                    if items.len() > MAX_ITEMS {
+                        ret += &format!(
+                            "\n{} more items match, use stricter filters to see them",
+                            items.len() - MAX_ITEMS
+                        );
+                    }
+                    let ret = Some(Cow::from(ret));
+                    if self.actions.force.is_some() {
+                        Err(ret)
+                    } else {
+                        Ok(ret)
+                    }
+                }
+            }
+            FtlActions::DebugHooks(event) => {
+                if !self.actions.valid(&event) {
+                    Err(Cow::from("debug actions are disabled").into())
+                } else if let Some(name) = event.name {
+                    if name == crate::GEN_INPUT_EVENTS_SIG.name {
+                        // the mod runs from this hook, it can't be turned back on once it's off
+                        Err(Cow::from(format!("{name} can't be disabled")).into())
+                    } else {
+                        let enabled = event.enabled.unwrap_or(true);
+                        // actions run in the main loop hook, outside of every other hook
+                        match unsafe { crate::cross::set_hook_enabled(&name, enabled) } {
+                            Ok(()) => Ok(Some(Cow::from(format!(
+                                "{name} {}",
+                                if enabled { "enabled" } else { "disabled" }
+                            )))),
+                            Err(err) => Err(Cow::from(err.to_string()).into()),
+                        }
+                    }
+                } else {
+                    let mut ret = String::from("Hooks:");
+                    for hook in crate::cross::hooks() {
+                        ret += &format!("\n{} ({:x}): ", hook.name, hook.offset);
+                        ret += match &hook.error {
+                            Some(err) => err,
+                            None if hook.enabled => "enabled",
+                            None => "disabled",
+                        };
+                    }
+                    Ok(Some(Cow::from(ret)))
+                }
+            }
             FtlActions::RememberShipInfo(event) => {
                 if !self.actions.valid(&event) {
                     Err(Cow::from("can't remember ship context at the moment").into())
@@ -3308,21 +3455,6 @@ impl neuro_sama::game::GameMut for State {
                     }
                 }
             }
-        };
-        if let Some(force) = &mut self.actions.force {
-            if ret.is_ok() {
-                self.actions.force = None;
-            } else if force.send_at.is_none() {
-                force.send_at = Some(Instant::now() + force.retry_timeout);
-            }
-        }
-        ret
-    }
-    fn reregister_actions(&mut self) {
-        if let Err(err) =
-            self.register_actions_raw(self.actions.actions.values().cloned().collect())
-        {
-            log::error!("error reregistering actions: {err}");
         }
     }
 }
@@ -3492,8 +3624,14 @@ fn iter_range<T: Copy + Ord>(it: impl Iterator<Item = T>) -> Option<std::ops::Ra
     })
 }
 
-fn available_actions(app: &CApp) -> ActionDb {
+fn available_actions(app: &CApp, outcomes: &outcomes::OutcomeCache) -> ActionDb {
     let mut ret = ActionDb::default();
+    if std::env::var_os("NEURO_FTL_DEBUG").is_some() {
+        ret.actions.insert(
+            actions::DebugHooks::name(),
+            meta_no_null::<actions::DebugHooks>(),
+        );
+    }
     if app.lang_chooser.base.b_open {
         // language selection is manual, while it's open don't allow neuro to do anything
         return ret;
@@ -3599,36 +3737,14 @@ fn available_actions(app: &CApp) -> ActionDb {
         return ret;
     }
     ret.add::<actions::Remember>();
-    {
-        let mut meta = meta::<actions::RememberShipInfo>();
-        let sch = meta.schema.as_object_mut().unwrap();
-        for prop in sch
-            .get_mut("properties")
-            .unwrap()
-            .as_object_mut()
-            .unwrap()
-            .values_mut()
-        {
-            let prop = prop.as_object_mut().unwrap();
-            let ty = prop.get_mut("type").unwrap();
-            let mut ty1 = serde_json::Value::Null;
-            std::mem::swap(&mut ty1, ty);
-            let mut remove_def = false;
-            *ty = match ty1 {
-                serde_json::Value::Array(x) => {
-                    remove_def = true;
-                    x.into_iter()
-                        .find(|x| x.as_str().unwrap() != "null")
-                        .unwrap()
-                }
-                x => x,
-            };
-            if remove_def {
-                prop.remove("default");
-            }
-        }
-        ret.actions.insert(actions::RememberShipInfo::name(), meta);
-    }
+    ret.actions.insert(
+        actions::RememberShipInfo::name(),
+        meta_no_null::<actions::RememberShipInfo>(),
+    );
+    ret.actions.insert(
+        actions::FindItems::name(),
+        meta_no_null::<actions::FindItems>(),
+    );
     if gui.leave_crew_dialog.base.b_open {
         if gui.leave_crew_dialog.yes_button.base.b_active {
             ret.add::<actions::Confirm>();
@@ -3690,6 +3806,7 @@ fn available_actions(app: &CApp) -> ActionDb {
     }
     if gui.choice_box.base.b_open {
         let c = &gui.choice_box;
+        let outcomes = outcomes::choice_outcomes(app, outcomes);
         for (i, choice) in c.choices.iter().enumerate() {
             let (name, mut meta) = match i {
                 0 => (actions::Choose1::name(), meta::<actions::Choose1>()),
@@ -3704,7 +3821,7 @@ fn available_actions(app: &CApp) -> ActionDb {
                 _ => panic!(),
             };
             meta.description = format!(
-                "Event option {}{}\n\n{}{}",
+                "Event option {}{}\n\n{}{}{}",
                 i + 1,
                 match choice.type_ {
                     1 => " (Requirements not met, cannot be chosen)",
@@ -3712,7 +3829,8 @@ fn available_actions(app: &CApp) -> ActionDb {
                     _ => " (No requirements)",
                 },
                 choice.text.to_str(),
-                resource_event_str(&choice.rewards, gui.ship_manager().unwrap())
+                resource_event_str(&choice.rewards, gui.ship_manager().unwrap()),
+                outcomes.get(i).map(String::as_str).unwrap_or_default(),
             )
             .into();
             ret.actions.insert(name, meta);
@@ -4062,7 +4180,7 @@ fn available_actions(app: &CApp) -> ActionDb {
             .unwrap()
             .systems()
             .flat_map(|x| System::from_id(x.i_system_type))
-            .map(|x| (map.map(x.blueprint().unwrap().title.to_str().into()), x))
+            .map(|x| (map.map(x.title().into()), x))
             .collect()
     });
 
@@ -4189,16 +4307,8 @@ fn available_actions(app: &CApp) -> ActionDb {
                         .systems()
                         .map(|x| {
                             serde_json::Value::String(
-                                map.map(
-                                    System::from_id(x.i_system_type)
-                                        .unwrap()
-                                        .blueprint()
-                                        .unwrap()
-                                        .title
-                                        .to_str()
-                                        .into(),
-                                )
-                                .into_owned(),
+                                map.map(System::from_id(x.i_system_type).unwrap().title().into())
+                                    .into_owned(),
                             )
                         })
                         .collect::<Vec<_>>()
@@ -4345,7 +4455,9 @@ fn room_desc(
     crew_map: &[Vec<String>],
     intruder_map: &[Vec<String>],
     system_map: &[Option<String>],
+    layout: Option<&crate::layout::Layout>,
 ) -> context::RoomInfo {
+    let geometry = layout.and_then(|x| x.room(room.i_room_id as u32));
     context::RoomInfo {
         faction: if room.i_ship_id == 0 {
             ShipId::Player
@@ -4353,6 +4465,19 @@ fn room_desc(
             ShipId::Enemy
         },
         room_id: room.i_room_id as u32,
+        grid_x: geometry.map(|x| x.x),
+        grid_y: geometry.map(|x| x.y),
+        width: geometry.map(|x| x.w),
+        height: geometry.map(|x| x.h),
+        neighbors: layout
+            .map(|x| x.neighbors(room.i_room_id as u32))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(room_id, direction)| context::RoomNeighbor {
+                room_id,
+                direction: direction.into(),
+            })
+            .collect(),
         doors: door_map
             .get(room.i_room_id as usize)
             .cloned()
@@ -4575,11 +4700,8 @@ fn crew_desc<'a>(crew: &'a bindings::CrewMember, map: &mut IdMap<'a>) -> context
         manning: crew
             .b_active_manning
             .then(|| {
-                unsafe { xc(crew.current_system) }.map(|system| {
-                    let sys = System::from_id(system.i_system_type).unwrap();
-                    let bp = sys.blueprint().unwrap();
-                    bp.title.to_str()
-                })
+                unsafe { xc(crew.current_system) }
+                    .map(|system| System::from_id(system.i_system_type).unwrap().title())
             })
             .flatten(),
         repairing: (!crew.current_repair.is_null() && crew.current_ship_id == crew.i_ship_id).then(
@@ -4587,7 +4709,7 @@ fn crew_desc<'a>(crew: &'a bindings::CrewMember, map: &mut IdMap<'a>) -> context
                 let repair = unsafe { xc(crew.current_repair).unwrap() };
                 let name = repair.name.to_str();
                 if let Some(sys) = System::from_name(&name) {
-                    sys.blueprint().unwrap().title.to_str()
+                    sys.title()
                 } else if &name == "Fire" {
                     "Fire"
                 } else if &name == "Algae" {
@@ -4605,7 +4727,7 @@ fn crew_desc<'a>(crew: &'a bindings::CrewMember, map: &mut IdMap<'a>) -> context
                 let repair = unsafe { xc(crew.current_repair).unwrap() };
                 let name = repair.name.to_str();
                 if let Some(sys) = System::from_name(&name) {
-                    sys.blueprint().unwrap().title.to_str()
+                    sys.title()
                 } else if &name == "Fire" {
                     "Fire"
                 } else if name.is_empty() {
@@ -5177,10 +5299,11 @@ fn system_desc(
     old_context: Option<&context::Context>,
 ) -> context::SystemInfo {
     let sys = System::from_id(system.i_system_type).unwrap();
-    let bp = sys.blueprint().unwrap();
+    // missing if the game data couldn't be loaded
+    let bp = sys.blueprint();
     let levels: Vec<_> = system_levels(
         sys,
-        &bp.upgrade_cost.levels,
+        bp.map_or(&[], |x| &x.upgrade_cost.levels),
         system.effective_power(),
         system.power_state.second,
     );
@@ -5206,11 +5329,11 @@ fn system_desc(
         } else {
             ShipId::Enemy
         },
-        cost: full_info.then_some(bp.cost as i32).unwrap_or_default(),
-        rarity: full_info.then_some(bp.rarity as i32).unwrap_or_default(),
+        cost: bp.filter(|_| full_info).map_or(0, |x| x.cost as i32),
+        rarity: bp.filter(|_| full_info).map_or(0, |x| x.rarity as i32),
         room_id: system.room_id.try_into().ok(),
-        system_name: map.map(bp.title.to_str().into()),
-        description: bp.desc.to_str().into(),
+        system_name: map.map(sys.title().into()),
+        description: bp.map_or("", |x| x.desc.to_str()).into(),
         tooltip: (sys != System::Artillery).then(|| sys.tooltip(system.i_ship_id != 0)),
         hp: Some(context::Pair {
             current: system.health_state.first,
@@ -5262,7 +5385,7 @@ fn system_desc(
         level: full_info
             .then_some(context::Pair {
                 current: system.power_state.second,
-                max: bp.max_power as i32,
+                max: bp.map_or(system.max_level, |x| x.max_power as i32),
             })
             .unwrap_or_default(),
         levels: full_info.then_some(levels).unwrap_or_default(),
@@ -5373,8 +5496,7 @@ fn system_desc(
                 system
                     .current_system()
                     .and_then(|x| System::from_id(x.i_system_type))
-                    .and_then(|x| x.blueprint())
-                    .map(|x| x.title.to_str())
+                    .map(|x| x.title())
             })
             .flatten(),
         battery_power: (full_info && sys == System::Battery).then(|| context::Pair {
@@ -5485,8 +5607,7 @@ fn system_bp_desc<'a>(
                 system
                     .current_system()
                     .and_then(|x| System::from_id(x.i_system_type))
-                    .and_then(|x| x.blueprint())
-                    .map(|x| x.title.to_str())
+                    .map(|x| x.title())
             })
             .flatten(),
         battery_power: (sys == System::Battery).then(|| context::Pair {
@@ -5645,9 +5766,7 @@ fn ship_manager_desc(
                         ret.resize(idx + 1, None);
                     }
                     let sys = System::from_id(system.i_system_type).unwrap();
-                    let bp = sys.blueprint().unwrap();
-                    *ret.get_mut(idx).unwrap() =
-                        Some(map.map(bp.title.to_str().into()).into_owned());
+                    *ret.get_mut(idx).unwrap() = Some(map.map(sys.title().into()).into_owned());
                 }
                 ret
             })
@@ -5680,6 +5799,7 @@ fn ship_manager_desc(
     {
         new_rooms.insert(x.i_room_id, x);
     }
+    let layout = crate::library().layout(&mgr.my_blueprint.layout_file.to_str());
     context::ShipInfo {
         destroyed: mgr.b_destroyed,
         hull: Help::new(text("tooltip_hull"), mgr.ship.hull_integrity.into()),
@@ -5720,6 +5840,7 @@ fn ship_manager_desc(
                         &crew_short,
                         &intruders_short,
                         &system_map,
+                        layout,
                     ))
                 }
             })
@@ -5829,8 +5950,7 @@ fn ship_manager_desc(
                         system_desc(mgr, system, map, sensors, old_context)
                     } else {
                         let sys = System::from_id(system.i_system_type).unwrap();
-                        let bp = sys.blueprint().unwrap();
-                        let name = map.map(bp.title.to_str().into());
+                        let name = map.map(sys.title().into());
                         let mut ret = if let Some(old) = old_systems.get(&name) {
                             (*old).clone()
                         } else {
@@ -5899,6 +6019,17 @@ impl From<bindings::Point> for context::Point<i32> {
     }
 }
 
+impl From<crate::xml::Direction> for context::Direction {
+    fn from(value: crate::xml::Direction) -> Self {
+        match value {
+            crate::xml::Direction::Up => context::Direction::Top,
+            crate::xml::Direction::Down => context::Direction::Bottom,
+            crate::xml::Direction::Left => context::Direction::Left,
+            crate::xml::Direction::Right => context::Direction::Right,
+        }
+    }
+}
+
 impl From<actions::Direction> for context::Direction {
     fn from(value: actions::Direction) -> Self {
         match value {
@@ -6079,12 +6210,21 @@ fn location(s: &bindings::StarMap, x: &bindings::Location) -> context::CurrentLo
 }
 
 fn sectors(s: &bindings::StarMap) -> Vec<context::SectorInfo> {
+    let lib = crate::library();
     let mut secs: Vec<_> = s
         .sectors
         .iter()
         .filter_map(|x| unsafe { xc(*x) })
         .filter(|x| x.reachable)
-        .map(|x| context::SectorInfo {
+        // unknown sectors don't reveal what's in them
+        .map(|x| {
+            (
+                x,
+                lib.sector(&x.description.type_.to_str())
+                    .filter(|_| x.type_ != 4),
+            )
+        })
+        .map(|(x, desc)| context::SectorInfo {
             map_position: x.location.into(),
             map_routes: x
                 .neighbors()
@@ -6092,16 +6232,62 @@ fn sectors(s: &bindings::StarMap) -> Vec<context::SectorInfo> {
                 .map(|(k, v)| (k.into(), unsafe { xc(v).unwrap() }.location.into()))
                 .collect(),
             sector_name: Some(x.description.name.to_str().into_owned()),
+            sector_type: Some(x.description.short_name.to_str().into_owned())
+                .filter(|name| !name.is_empty() && x.type_ != 4),
             hostile: x.type_ == 1,
             civilian: x.type_ == 0,
             nebula: x.type_ == 2,
             unknown: x.type_ == 4,
+            expected_beacons: desc
+                .map(|desc| sectors::expected_beacons(lib, desc, x.level as u32))
+                .unwrap_or_default(),
+            common_items: desc
+                .map(|desc| sectors::common_items(lib, desc))
+                .unwrap_or_default(),
         })
         .collect();
     secs.sort_by_key(|sec| (sec.map_position.x, sec.map_position.y));
     secs
 }
 
+/// All achievements the game tracks, including the ship unlocks
+fn tracked_achievements() -> impl Iterator<Item = &'static bindings::CAchievement> {
+    let tracker = crate::achievement_tracker();
+    tracker
+        .achievements
+        .iter()
+        .chain(tracker.ship_unlocks.iter().flat_map(|x| x.iter()))
+        .filter_map(|x| unsafe { xc(*x) })
+}
+
+fn achievements() -> Vec<context::AchievementInfo> {
+    let lib = crate::library();
+    let tracked: HashMap<_, _> = tracked_achievements()
+        .map(|x| (x.name_id.to_str(), x))
+        .collect();
+    lib.achievements()
+        .iter()
+        .map(|x| {
+            let tracked = tracked.get(x.id.as_str());
+            context::AchievementInfo {
+                achievement_name: lib.text_str(&x.name).unwrap_or(&x.id).to_owned(),
+                description: lib.text_str(&x.desc).unwrap_or_default().to_owned(),
+                ship_name: x
+                    .ship
+                    .as_ref()
+                    .map(|s| lib.blueprint_name(s).unwrap_or(s).to_owned()),
+                unlocked: tracked.is_some_and(|x| x.unlocked),
+                progress: tracked
+                    .filter(|x| x.progress.second > 0)
+                    .map(|x| context::Pair {
+                        current: x.progress.first,
+                        max: x.progress.second,
+                    }),
+            }
+        })
+        .collect()
+}
+
 fn inventory(gui: &bindings::CommandGui) -> context::Inventory {
     let mgr = gui.ship_manager().unwrap();
     context::Inventory {
@@ -6287,8 +6473,17 @@ fn store_page(app: &bindings::CApp, force: bool) -> Option<context::StoreItems>
         .flatten()
 }
 
-fn event_options(gui: &bindings::CommandGui) -> (Option<String>, VecDeque<String>) {
+fn event_options(
+    app: &CApp,
+    outcomes: &outcomes::OutcomeCache,
+) -> (Option<String>, VecDeque<String>) {
+    let gui = app.gui().unwrap();
     let c = &gui.choice_box;
+    let outcomes = if c.base.b_open {
+        outcomes::choice_outcomes(app, outcomes)
+    } else {
+        Vec::new()
+    };
     c.base
         .b_open
         .then(|| {
@@ -6303,7 +6498,7 @@ fn event_options(gui: &bindings::CommandGui) -> (Option<String>, VecDeque<String
                     .enumerate()
                     .map(|(i, choice)| {
                         format!(
-                            "Event option {}{}\n\n{}{}",
+                            "Event option {}{}\n\n{}{}{}",
                             i + 1,
                             match choice.type_ {
                                 1 => " (Requirements not met, cannot be chosen)",
@@ -6311,7 +6506,8 @@ fn event_options(gui: &bindings::CommandGui) -> (Option<String>, VecDeque<String
                                 _ => " (No requirements)",
                             },
                             choice.text.to_str(),
-                            resource_event_str(&choice.rewards, gui.ship_manager().unwrap())
+                            resource_event_str(&choice.rewards, gui.ship_manager().unwrap()),
+                            outcomes.get(i).map(String::as_str).unwrap_or_default(),
                         )
                     })
                     .collect(),
@@ -6329,6 +6525,25 @@ fn collect_context(
     context::Context,
 ) {
     let mut events = Vec::new();
+    let unlocked: HashSet<String> = tracked_achievements()
+        .filter(|x| x.unlocked)
+        .map(|x| x.name_id.to_str().into_owned())
+        .collect();
+    if let Some(old) = &game.unlocked_achievements {
+        for x in tracked_achievements() {
+            if x.unlocked && !old.contains(x.name_id.to_str().as_ref()) {
+                events.push(
+                    format!(
+                        "Achievement unlocked: {} ({})",
+                        x.name.to_str(),
+                        x.description.to_str()
+                    )
+                    .into(),
+                );
+            }
+        }
+    }
+    game.unlocked_achievements = Some(unlocked);
     for (ship, _, weapon) in game.shot_q.drain(..) {
         if ship == 0 {
             events.push(format!("A player weapon {weapon:?} has fired a shot").into());
@@ -6592,7 +6807,7 @@ fn collect_context(
         tips.push("Your action is awaiting confirmation".into());
         confirmation_message = gui.crew_screen.delete_dialog.text.to_str().into_owned();
     }
-    let (event_text, event_options) = event_options(gui);
+    let (event_text, event_options) = event_options(app, &game.outcomes);
     let mgr = gui.ship_manager().unwrap();
     let has_confirm = !confirmation_message.is_empty();
     let ret = context::Context {
@@ -6623,6 +6838,7 @@ fn collect_context(
                 sectors(s)
             })
             .unwrap_or_default(),
+        achievements: vec![],
         selected_ship: None,
         victory: None,
         current_store_page: event_text
@@ -6834,6 +7050,9 @@ pub fn loop_hook2(app: &mut CApp) {
                 pulsar_q: vec![],
                 sun_q: vec![],
                 shot_q: vec![],
+                unlocked_achievements: None,
+                history: VecDeque::new(),
+                outcomes: Default::default(),
             };
             let rt = tokio::runtime::Builder::new_current_thread()
                 .enable_all()
@@ -6896,7 +7115,7 @@ pub fn loop_hook2(app: &mut CApp) {
     }
     let game = unsafe { GAME.get_mut().unwrap() };
     game.app = app;
-    let actions = available_actions(app);
+    let actions = available_actions(app, &game.outcomes);
     let mut to_remove = Vec::new();
     game.actions.actions.retain(|k, v| {
         if !matches!(actions.actions.get(*k), Some(x) if x == v) {
@@ -7073,6 +7292,7 @@ pub fn activated() -> bool {
 pub fn deactivate() {
     log::error!("deactivating");
     DEACTIVATE.get_or_init(|| {
+        crash::write_report(unsafe { GAME.get() });
         if let Some(game) = unsafe { GAME.get_mut() } {
             game.context("the mod just crashed... the game may or may not be still running, but you can no longer control it", false)
                 .unwrap();
@@ -7081,29 +7301,94 @@ pub fn deactivate() {
     });
 }
 
+/// How many panics are tolerated within [`PANIC_WINDOW`] before the mod deactivates
+const MAX_PANICS: usize = 3;
+const PANIC_WINDOW: Duration = Duration::from_secs(60);
+
+impl State {
+    /// Drop everything that may be left over from a frame that panicked halfway, so the next
+    /// frame sends the full state and all actions again
+    fn reset(&mut self) {
+        crash::clear_panic();
+        self.cooldown = None;
+        self.buffer = None;
+        self.ship_hits_q.clear();
+        self.drone_hits_q.clear();
+        self.pulsar_q.clear();
+        self.sun_q.clear();
+        self.projectile_stack.clear();
+        self.shot_q.clear();
+        self.outcomes = Default::default();
+        let names: Vec<_> = self.actions.actions.keys().map(|x| Cow::from(*x)).collect();
+        self.actions = ActionDb::default();
+        if !names.is_empty() {
+            if let Err(err) = self.unregister_actions_raw(names) {
+                log::error!("error unregistering actions: {err}");
+            }
+        }
+        if let Err(err) = self.context(
+            "the mod ran into an error and had to resynchronize, the last action may or may not have happened. The full game state will be sent again",
+            false,
+        ) {
+            log::error!("error sending context: {err}");
+        }
+    }
+}
+
+/// Record a panic, returns `false` if there were too many of them recently
+fn panic_budget() -> bool {
+    static PANICS: Mutex<VecDeque<Instant>> = Mutex::new(VecDeque::new());
+    let mut panics = PANICS.lock().unwrap();
+    let now = Instant::now();
+    panics.retain(|x| now.duration_since(*x) < PANIC_WINDOW);
+    panics.push_back(now);
+    panics.len() <= MAX_PANICS
+}
+
 pub unsafe fn loop_hook(app: *mut CApp) {
     if !activated() {
         return;
     }
     std::panic::set_hook(Box::new(|info| {
-        if let Some(s) = info.payload().downcast_ref::<&str>() {
-            log::error!("panic occurred: {s}");
+        let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+            s.to_string()
         } else if let Some(s) = info.payload().downcast_ref::<String>() {
-            log::error!("panic occurred: {s}");
+            s.clone()
         } else {
-            log::error!("panic occurred");
-        }
-        let backtrace = std::backtrace::Backtrace::capture();
+            String::new()
+        };
+        let message = match info.location() {
+            Some(loc) => format!("{message} at {loc}"),
+            None => message,
+        };
+        log::error!("panic occurred: {message}");
+        let backtrace = std::backtrace::Backtrace::force_capture();
         log::error!("Backtrace:\n{:#?}", backtrace);
+        crash::record_panic(crash::PanicInfo {
+            message,
+            backtrace: backtrace.to_string(),
+        });
     }));
     if !app.is_null() {
+        bindings::take_invalid_pointer();
         #[allow(clippy::blocks_in_conditions)]
         if std::panic::catch_unwind(|| {
             loop_hook2(&mut *app);
         })
         .is_err()
         {
-            deactivate();
+            let invalid_pointer = bindings::take_invalid_pointer();
+            if !panic_budget() {
+                log::error!("too many errors in the last {PANIC_WINDOW:?}");
+                deactivate();
+            } else if let Some(ptr) = invalid_pointer {
+                // the pointer may be valid again next frame, e.g. if it was read mid-update
+                log::warn!("skipping a frame because of an invalid pointer {ptr:#x}");
+                crash::clear_panic();
+            } else if let Some(game) = GAME.get_mut() {
+                log::warn!("resetting the mod's state");
+                game.reset();
+            }
         }
     }
 }
@@ -7212,3 +7497,94 @@ pub unsafe fn pulsar_damage(this: *mut bindings::ShipManager) {
     let this = xc(this).unwrap();
     game.pulsar_q.push(this.i_ship_id);
 }
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::{HashMap, VecDeque},
+        ptr,
+    };
+
+    use tokio::sync::mpsc;
+
+    use super::{
+        actions::{self, FtlActions},
+        bindings::{self, CApp},
+        crash, panic_budget, ActionDb, Cow, State, MAX_PANICS,
+    };
+
+    /// A state that isn't connected to the game, and the receiving end of its websocket channel
+    pub(super) fn state() -> (State, mpsc::Receiver<tungstenite::Message>) {
+        let (tx, game2ws) = mpsc::channel(8);
+        let state = State {
+            cooldown: None,
+            tx,
+            rx: mpsc::channel(1).1,
+            app: ptr::null_mut(),
+            actions: ActionDb::default(),
+            buffer: None,
+            tips: HashMap::new(),
+            drone_hits_q: vec![],
+            projectiles: HashMap::new(),
+            projectile_stack: vec![ptr::null_mut()],
+            ship_hits_q: vec![(0, None, true)],
+            pulsar_q: vec![1],
+            sun_q: vec![],
+            shot_q: vec![],
+            unlocked_achievements: None,
+            history: VecDeque::new(),
+            outcomes: Default::default(),
+        };
+        (state, game2ws)
+    }
+
+    #[test]
+    fn test_recovery() {
+        let (mut state, mut game2ws) = state();
+        state.actions.add::<super::actions::Wait>();
+        crash::record_panic(crash::PanicInfo {
+            message: "test panic".to_owned(),
+            backtrace: String::new(),
+        });
+        state.reset();
+        assert!(crash::last_panic().is_none());
+        assert!(state.actions.actions.is_empty());
+        assert!(state.projectile_stack.is_empty());
+        assert!(state.ship_hits_q.is_empty());
+        assert!(state.pulsar_q.is_empty());
+        let unregister = game2ws.try_recv().unwrap().into_text().unwrap();
+        assert!(unregister.contains("actions/unregister"), "{unregister}");
+        let context = game2ws.try_recv().unwrap().into_text().unwrap();
+        assert!(context.contains("resynchronize"), "{context}");
+
+        for _ in 0..MAX_PANICS {
+            assert!(panic_budget());
+        }
+        assert!(!panic_budget());
+    }
+
+    #[test]
+    fn test_invalid_pointer_action() {
+        use neuro_sama::game::GameMut;
+
+        let (mut state, _game2ws) = state();
+        let mut app = Box::<CApp>::new_zeroed();
+        // `gui` points to unmapped memory, the action's `unwrap` on it has to fail the action
+        unsafe { (*app.as_mut_ptr()).gui = 0x1000 as *mut _ };
+        state.app = app.as_mut_ptr();
+        state.actions.add::<actions::Wait>();
+
+        let _checks = bindings::test::CheckPointers::new();
+        let ret = state.handle_action(FtlActions::Wait(actions::Wait {
+            distress_signal: false,
+        }));
+        let Err(Some(err)) = ret else {
+            panic!("the action didn't fail");
+        };
+        let err: Cow<str> = err.into();
+        assert!(err.contains("memory at 0x1000 is invalid"), "{err}");
+        assert!(!state.history.back().unwrap().success);
+        assert!(crash::last_panic().is_none());
+        assert_eq!(bindings::take_invalid_pointer(), None);
+    }
+}
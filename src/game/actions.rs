@@ -3,6 +3,8 @@ use neuro_sama::derive::Actions;
 use schemars::JsonSchema;
 use serde::Deserialize;
 
+use crate::blueprints::BlueprintKind;
+
 // a SystemName without is_referenceable, so it isn't put in $ref, to make the schema simpler
 /*#[derive(Copy, Clone, Debug, Deserialize, JsonSchemaNoRef)]
 #[serde(rename_all = "snake_case")]
@@ -421,6 +423,8 @@ pub enum RememberType {
     #[allow(unused)]
     CurrentEvent,
     #[allow(unused)]
+    Achievements,
+    #[allow(unused)]
     Everything,
 }
 
@@ -452,6 +456,33 @@ pub struct RememberShipInfo {
     pub augment_names: Option<Vec<String>>,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FindItems {
+    #[serde(default)]
+    pub kind: Option<BlueprintKind>,
+    #[serde(default)]
+    pub min_cost: Option<u32>,
+    #[serde(default)]
+    pub max_cost: Option<u32>,
+    #[serde(default)]
+    pub min_damage: Option<u32>,
+    #[serde(default)]
+    pub max_rarity: Option<u32>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub include_unavailable: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct DebugHooks {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Actions, Debug)]
 pub enum FtlActions {
@@ -708,4 +739,15 @@ pub enum FtlActions {
     /// info is sent - for example, {"weaponNames":[]} will send info about all weapons.
     #[name = "remember_ship_info"]
     RememberShipInfo(RememberShipInfo),
+    /// Search the game's items by their stats, for example to find out what's worth buying. All
+    /// fields are optional. `tags` are the item's type and special effects, such as `laser`,
+    /// `beam`, `missiles`, `flak`, `bomb`, `ion`, `fire`, `breach`, `stun`, `shield_piercing` or
+    /// `crew_damage` for weapons, and `combat`, `defense`, `repair` or `boarder` for drones. Rarity
+    /// goes from 1 (common) to 5 (rare), items that can't be bought in stores are only listed if
+    /// `include_unavailable` is true.
+    #[name = "find_items"]
+    FindItems(FindItems),
+    /// Debugging only: list the mod's hooks, or enable or disable the hook called `name`.
+    #[name = "debug_hooks"]
+    DebugHooks(DebugHooks),
 }
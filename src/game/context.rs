@@ -337,6 +337,16 @@ pub struct DoorInfoShort {
     pub room_id: i32,
 }
 
+#[derive(Clone, Debug, Delta, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RoomNeighbor {
+    #[delta1]
+    pub room_id: u32,
+    /// which side of this room the neighbor is on
+    #[delta2]
+    pub direction: Direction,
+}
+
 #[derive(Clone, Debug, Delta, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct RoomInfo {
@@ -344,6 +354,19 @@ pub struct RoomInfo {
     pub faction: ShipId,
     #[delta1]
     pub room_id: u32,
+    /// position of the top left corner on the ship's grid, x grows to the right, y grows down
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_x: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_y: Option<i32>,
+    /// size in grid cells
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i32>,
+    /// rooms sharing a wall with this one, whether or not there's a door
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub neighbors: Vec<RoomNeighbor>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub doors: Vec<DoorInfoShort>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -693,6 +716,8 @@ pub struct Context {
     pub locations: Vec<LocationInfo>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub sectors: Vec<SectorInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub achievements: Vec<AchievementInfo>,
     #[serde(skip_serializing_if = "is_zero")]
     pub in_main_menu: bool,
     #[serde(skip_serializing_if = "is_zero")]
@@ -933,6 +958,9 @@ pub struct SectorInfo {
     // only add this if this is immediately reachable
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sector_name: Option<String>,
+    /// short title of the sector's description, e.g. "Engi" or "Pirate"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sector_type: Option<String>,
     pub map_routes: BTreeMap<Direction, Point<i32>>,
     #[serde(skip_serializing_if = "is_zero")]
     pub hostile: bool,
@@ -942,6 +970,29 @@ pub struct SectorInfo {
     pub nebula: bool,
     #[serde(skip_serializing_if = "is_zero")]
     pub unknown: bool,
+    /// what beacons the sector usually has, e.g. "2-4 hostile ships"
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub expected_beacons: Vec<String>,
+    /// items that are found more often than usual in this sector
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub common_items: Vec<String>,
+}
+
+#[derive(Clone, Debug, Delta, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AchievementInfo {
+    #[delta1]
+    pub achievement_name: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// the ship this achievement unlocks a layout of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ship_name: Option<String>,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub unlocked: bool,
+    /// only for achievements that are unlocked gradually
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<Pair<i32>>,
 }
 
-impl_delta!(ShipId, Species, InventorySlotType);
+impl_delta!(ShipId, Species, InventorySlotType, Direction);
@@ -0,0 +1,217 @@
+//! Crash reports written when the mod deactivates, with everything needed to reproduce the bug
+use std::{
+    collections::VecDeque,
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use super::{
+    context::util::{SerContext, Serializable},
+    State,
+};
+use crate::cross;
+
+/// How many of the last actions are kept for the report
+pub const HISTORY_LEN: usize = 20;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ActionRecord {
+    pub action: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PanicInfo {
+    pub message: String,
+    pub backtrace: String,
+}
+
+static LAST_PANIC: Mutex<Option<PanicInfo>> = Mutex::new(None);
+
+/// Remember a panic for the next report
+pub fn record_panic(info: PanicInfo) {
+    *LAST_PANIC.lock().unwrap() = Some(info);
+}
+
+/// Forget the last panic once the mod recovered from it, so it isn't blamed for a later crash
+pub fn clear_panic() {
+    *LAST_PANIC.lock().unwrap() = None;
+}
+
+pub fn last_panic() -> Option<PanicInfo> {
+    LAST_PANIC.lock().unwrap().clone()
+}
+
+pub fn record_action(history: &mut VecDeque<ActionRecord>, record: ActionRecord) {
+    if history.len() >= HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(record);
+}
+
+#[derive(Serialize)]
+struct Hook {
+    name: &'static str,
+    offset: String,
+    enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Resolved {
+    name: &'static str,
+    offset: String,
+    resolution: String,
+}
+
+#[derive(Serialize)]
+struct Report<'a, C: Serialize> {
+    version: &'static str,
+    /// unix time
+    time: u64,
+    /// `None` if the game itself crashed rather than the mod panicking
+    panic: Option<PanicInfo>,
+    context: Option<C>,
+    actions: &'a VecDeque<ActionRecord>,
+    registered_actions: Vec<&'static str>,
+    hooks: Vec<Hook>,
+    /// how the bindings with a signature were found
+    signatures: Vec<Resolved>,
+}
+
+fn report(
+    state: Option<&State>,
+    panic: Option<PanicInfo>,
+    time: u64,
+) -> serde_json::Result<String> {
+    let empty = VecDeque::new();
+    let report = Report {
+        version: env!("CARGO_PKG_VERSION"),
+        time,
+        panic,
+        context: state
+            .and_then(|x| x.buffer.as_ref())
+            .map(|x| x.serializable(&mut SerContext::default())),
+        actions: state.map_or(&empty, |x| &x.history),
+        registered_actions: state
+            .map_or_else(Vec::new, |x| x.actions.actions.keys().copied().collect()),
+        hooks: cross::hooks()
+            .into_iter()
+            .map(|x| Hook {
+                name: x.name,
+                offset: format!("{:x}", x.offset),
+                enabled: x.enabled,
+                error: x.error,
+            })
+            .collect(),
+        signatures: cross::report()
+            .into_iter()
+            .map(|x| Resolved {
+                name: x.name,
+                offset: format!("{:x}", x.offset),
+                resolution: format!("{:?}", x.resolution),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&report)
+}
+
+/// Where reports go, `NEURO_FTL_CRASH_DIR` or the working directory
+fn report_dir() -> PathBuf {
+    std::env::var_os("NEURO_FTL_CRASH_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn write_report_to(
+    dir: &Path,
+    state: Option<&State>,
+    panic: Option<PanicInfo>,
+) -> io::Result<PathBuf> {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs());
+    let text = report(state, panic, time)?;
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("neuro-ftl-crash-{time}.json"));
+    std::fs::write(&path, text)?;
+    Ok(path)
+}
+
+pub fn write_report(state: Option<&State>) {
+    match write_report_to(&report_dir(), state, last_panic()) {
+        Ok(path) => log::error!("crash report written to {}", path.display()),
+        Err(err) => log::error!("failed to write a crash report: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::VecDeque, path::Path};
+
+    use super::{record_action, write_report_to, ActionRecord, PanicInfo};
+    use crate::game::{actions, context, State};
+
+    fn read_report(
+        dir: &Path,
+        state: Option<&State>,
+        panic: Option<PanicInfo>,
+    ) -> serde_json::Value {
+        let path = write_report_to(dir, state, panic).unwrap();
+        let report = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+        report
+    }
+
+    #[test]
+    fn test_report() {
+        let mut history = VecDeque::new();
+        for i in 0..super::HISTORY_LEN + 5 {
+            record_action(
+                &mut history,
+                ActionRecord {
+                    action: format!("Wait({i})"),
+                    success: true,
+                    message: None,
+                },
+            );
+        }
+        assert_eq!(history.len(), super::HISTORY_LEN);
+        assert_eq!(history[0].action, "Wait(5)");
+
+        let dir = std::env::temp_dir().join(format!("neuro-ftl-test-{}", std::process::id()));
+        let panic = PanicInfo {
+            message: "test panic".to_owned(),
+            backtrace: String::new(),
+        };
+        let report = read_report(&dir, None, Some(panic));
+        assert_eq!(report["panic"]["message"], "test panic");
+        assert_eq!(report["context"], serde_json::Value::Null);
+        assert!(report["hooks"].is_array());
+        assert!(report["signatures"].is_array());
+
+        let (mut state, _game2ws) = crate::game::test::state();
+        state.buffer = Some(context::Context {
+            event_text: Some("A ship appears".to_owned()),
+            ..Default::default()
+        });
+        state.history = history;
+        state.actions.add::<actions::Wait>();
+        let report = read_report(&dir, Some(&state), None);
+        assert_eq!(report["panic"], serde_json::Value::Null);
+        assert_eq!(report["context"]["eventText"], "A ship appears");
+        assert_eq!(
+            report["actions"].as_array().unwrap().len(),
+            super::HISTORY_LEN
+        );
+        assert_eq!(report["actions"][0]["action"], "Wait(5)");
+        assert_eq!(report["registered_actions"].as_array().unwrap().len(), 1);
+    }
+}
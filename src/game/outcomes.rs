@@ -0,0 +1,552 @@
+//! Predicting what event choices lead to, based on the game's event XML.
+use std::{
+    cell::RefCell,
+    collections::{HashSet, VecDeque},
+};
+
+use crate::{bindings::CApp, xml, Blueprint, TextLibrary};
+
+fn text_matches(lib: &TextLibrary, shown: &str, s: &xml::TextString) -> bool {
+    lib.text_str(s).is_some_and(|x| {
+        let x = x.trim();
+        // blue options are prefixed with the requirement
+        !x.is_empty() && (shown == x || shown.ends_with(x))
+    })
+}
+
+/// Map the choices shown in the choice box to the event's choices. Returns the number of choices
+/// that were matched by text, or `None` if none were.
+fn match_choices<'a>(
+    lib: &TextLibrary,
+    event: &'a xml::Event,
+    choices: &[&str],
+) -> Option<(usize, Vec<Option<&'a xml::Choice>>)> {
+    let mut used = vec![false; event.choices.len()];
+    let mut matched = 0;
+    let mut ret: Vec<_> = choices
+        .iter()
+        .map(|shown| {
+            let (i, choice) = event
+                .choices
+                .iter()
+                .enumerate()
+                .find(|(i, x)| !used[*i] && text_matches(lib, shown.trim(), &x.text))?;
+            used[i] = true;
+            matched += 1;
+            Some(choice)
+        })
+        .collect();
+    if matched == 0 {
+        return None;
+    }
+    // choices with random text can't be matched, but if nothing was hidden the order is the same
+    if event.choices.len() == choices.len() {
+        for (i, x) in ret.iter_mut().enumerate() {
+            if x.is_none() && !used[i] {
+                *x = Some(&event.choices[i]);
+            }
+        }
+    }
+    Some((matched, ret))
+}
+
+/// Find the event whose choices are currently shown. `name` is the name the game generated the
+/// event from, which may be a parent event or an event list. If it's unknown, every event is
+/// searched.
+pub(crate) fn find_event<'a>(
+    lib: &'a TextLibrary,
+    name: &str,
+    main_text: &str,
+    choices: &[&str],
+) -> Option<Vec<Option<&'a xml::Choice>>> {
+    // event lists and "continue" choices often lead back to earlier events
+    let mut visited = HashSet::new();
+    let mut q: VecDeque<(usize, &xml::Event)> = lib
+        .resolve_event_name(name)
+        .into_iter()
+        .map(|(_, x)| (0, x))
+        .filter(|(_, x)| visited.insert(*x as *const xml::Event))
+        .collect();
+    if q.is_empty() {
+        q.extend(
+            lib.events
+                .values()
+                .map(|x| (0, x))
+                .filter(|(_, x)| visited.insert(*x as *const xml::Event)),
+        );
+    }
+    let mut best = None;
+    let mut best_score = 0;
+    while let Some((depth, event)) = q.pop_front() {
+        if let Some((matched, ret)) = match_choices(lib, event, choices) {
+            let mut score = matched * 2;
+            if event.text.as_ref().is_some_and(|x| {
+                lib.text_str(x)
+                    .is_some_and(|x| x.trim() == main_text.trim())
+            }) {
+                score += 1;
+            }
+            if score > best_score {
+                best_score = score;
+                best = Some(ret);
+            }
+        }
+        if depth < 8 {
+            for choice in &event.choices {
+                q.extend(
+                    lib.resolve_event(&choice.event)
+                        .into_iter()
+                        .map(|(_, x)| (depth + 1, x))
+                        .filter(|(_, x)| visited.insert(*x as *const xml::Event)),
+                );
+            }
+        }
+    }
+    best
+}
+
+fn blueprint_name<'a>(lib: &'a TextLibrary, name: &'a str) -> &'a str {
+    lib.blueprint_name(name).unwrap_or(name)
+}
+
+/// Name of an item along with its most important stats, e.g. "Heavy Laser (2 damage, 1 power, 9s
+/// cooldown, 55 scrap)"
+fn describe_blueprint(lib: &TextLibrary, name: &str) -> String {
+    let mut stats = Vec::new();
+    let cost = match lib.blueprints.get(name) {
+        Some(Blueprint::Weapon(b)) => {
+            if b.damage > 0 {
+                stats.push(format!("{} damage", b.damage));
+            }
+            if let Some(ion) = b.ion.filter(|x| *x > 0) {
+                stats.push(format!("{ion} ion damage"));
+            }
+            if let Some(shots) = b.shots.filter(|x| *x > 1) {
+                stats.push(format!("{shots} shots"));
+            }
+            if b.missiles.is_some_and(|x| x > 0) {
+                stats.push("uses missiles".to_owned());
+            }
+            if let Some(power) = b.power {
+                stats.push(format!("{power} power"));
+            }
+            if let Some(cooldown) = b.cooldown {
+                stats.push(format!("{cooldown}s cooldown"));
+            }
+            b.cost
+        }
+        Some(Blueprint::Drone(b)) => {
+            stats.push(format!("{} power", b.power));
+            Some(b.cost)
+        }
+        Some(Blueprint::Augment(b)) => Some(b.cost),
+        Some(Blueprint::Crew(b)) => Some(b.cost),
+        Some(Blueprint::System(b)) => Some(b.cost),
+        Some(Blueprint::Item(b)) => Some(b.cost),
+        Some(Blueprint::Ship(_)) | None => None,
+    };
+    if let Some(cost) = cost {
+        stats.push(format!("{cost} scrap"));
+    }
+    let name = blueprint_name(lib, name);
+    if stats.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{name} ({})", stats.join(", "))
+    }
+}
+
+/// Describe the items a blueprint list can give, capped so huge lists don't flood the context
+fn describe_blueprint_list(lib: &TextLibrary, name: &str) -> String {
+    const MAX_ITEMS: usize = 10;
+    let items = lib.resolve_blueprint_list(name);
+    let mut ret = items
+        .iter()
+        .take(MAX_ITEMS)
+        .map(|x| describe_blueprint(lib, x))
+        .collect::<Vec<_>>()
+        .join("; ");
+    if items.len() > MAX_ITEMS {
+        ret += &format!("; or one of {} other items", items.len() - MAX_ITEMS);
+    }
+    ret
+}
+
+fn lowercase(x: impl std::fmt::Debug) -> String {
+    format!("{x:?}").to_lowercase()
+}
+
+/// Describe what happens when an event is reached, in the same style as `resource_event_str`.
+fn describe_event(lib: &TextLibrary, event: &xml::Event) -> String {
+    let mut ret = Vec::new();
+    if let Some(ship) = &event.ship {
+        let name = lib
+            .ship(ship)
+            .and_then(|x| x.auto_blueprint.as_ref().or(x.blueprint.as_ref()))
+            .map(|x| format!(" ({})", blueprint_name(lib, x)))
+            .unwrap_or_default();
+        if ship.hostile {
+            ret.push(format!("a hostile ship{name} will attack you"));
+        } else {
+            ret.push(format!("a ship{name} will appear"));
+        }
+    }
+    if let Some(b) = &event.boarders {
+        let class = b
+            .class
+            .as_deref()
+            .map(|x| format!(" {x}"))
+            .unwrap_or_default();
+        if b.min == b.max {
+            ret.push(format!("{}{class} intruders will board your ship", b.min));
+        } else {
+            ret.push(format!(
+                "{}-{}{class} intruders will board your ship",
+                b.min, b.max
+            ));
+        }
+    }
+    for d in &event.damage {
+        let target = d
+            .system
+            .as_ref()
+            .map(|x| format!(" ({} system)", lowercase(x)))
+            .unwrap_or_default();
+        match d.amount {
+            1.. => ret.push(format!("ship hull will take {} damage{target}", d.amount)),
+            ..=-1 => ret.push(format!("ship hull will be repaired by {}", -d.amount)),
+            0 => {}
+        }
+        match d.effect {
+            Some(xml::DamageEffect::Fire) => ret.push(format!("a fire will start{target}")),
+            Some(xml::DamageEffect::Breach) => ret.push(format!("a hull breach will open{target}")),
+            _ => {}
+        }
+    }
+    if let Some(items) = &event.item_modify {
+        for item in &items.item {
+            let ty = lowercase(&item.type_);
+            let ty = if ty == "drones" { "drone parts" } else { &ty };
+            let amount = |a: i32, b: i32| {
+                if a == b {
+                    a.to_string()
+                } else {
+                    format!("{a}-{b}")
+                }
+            };
+            if item.min >= 0 {
+                ret.push(format!("will get {} {ty}", amount(item.min, item.max)));
+            } else if item.max <= 0 {
+                ret.push(format!("will lose {} {ty}", amount(-item.max, -item.min)));
+            } else {
+                ret.push(format!("will get or lose {}-{} {ty}", item.min, item.max));
+            }
+        }
+    }
+    for (what, x) in [
+        ("a weapon", &event.weapon),
+        ("a drone", &event.drone),
+        ("an augment", &event.augment),
+    ] {
+        if let Some(x) = x {
+            if x.name == "RANDOM" {
+                ret.push(format!("will get {what} (random)"));
+            } else if lib.blueprint_list(&x.name).is_some() {
+                ret.push(format!(
+                    "will get {what}, one of: {}",
+                    describe_blueprint_list(lib, &x.name)
+                ));
+            } else {
+                ret.push(format!(
+                    "will get {what} ({})",
+                    blueprint_name(lib, &x.name)
+                ));
+            }
+        }
+    }
+    if let Some(x) = &event.remove {
+        ret.push(format!(
+            "item will be removed ({})",
+            blueprint_name(lib, &x.name)
+        ));
+    }
+    if let Some(crew) = &event.crew_member {
+        let class = crew
+            .class
+            .as_deref()
+            .map(|x| format!(" ({})", blueprint_name(lib, x)))
+            .unwrap_or_default();
+        match crew.amount {
+            1 => ret.push(format!("will gain a crew member{class}")),
+            2.. => ret.push(format!("will gain {} crew members{class}", crew.amount)),
+            ..=-1 => ret.push(format!("a crew member will die{class}")),
+            0 => {}
+        }
+    }
+    if event.remove_crew.is_some() {
+        ret.push("a crew member will be lost".to_owned());
+    }
+    if let Some(x) = &event.auto_reward {
+        ret.push(format!(
+            "will get a {} reward ({})",
+            lowercase(&x.level),
+            x.value.replace('_', " ")
+        ));
+    }
+    if let Some(x) = &event.upgrade {
+        ret.push(format!("{} will be upgraded", lowercase(&x.system)));
+    }
+    if let Some(x) = &event.modiy_pursuit {
+        match x.amount {
+            1.. => ret.push(format!("rebel fleet will be delayed by {} jumps", x.amount)),
+            ..=-1 => ret.push(format!(
+                "rebel fleet pursuit speed will be doubled for {} jumps",
+                -x.amount
+            )),
+            0 => {}
+        }
+    }
+    if let Some(x) = &event.environment {
+        ret.push(format!(
+            "the beacon will have a {} hazard",
+            lowercase(&x.type_)
+        ));
+    }
+    if event.store.is_some() {
+        ret.push("a store will be available".to_owned());
+    }
+    if event.repair.is_some() {
+        ret.push("ship can be repaired".to_owned());
+    }
+    if event.quest.is_some() {
+        ret.push("a quest beacon will be marked on the map".to_owned());
+    }
+    if event.distress_beacon.is_some() {
+        ret.push("a distress beacon will be placed".to_owned());
+    }
+    if event.reveal_map.is_some() {
+        ret.push("the sector map will be revealed".to_owned());
+    }
+    if event.secret_sector.is_some() {
+        ret.push("will jump to a secret sector".to_owned());
+    }
+    if !event.choices.is_empty() {
+        ret.push(format!(
+            "a follow-up event with {} options",
+            event.choices.len()
+        ));
+    }
+    if ret.is_empty() {
+        "nothing happens".to_owned()
+    } else {
+        ret.join(", ")
+    }
+}
+
+/// Describe the possible outcomes of a choice, grouping identical outcomes together.
+pub(crate) fn describe_choice(lib: &TextLibrary, choice: &xml::Choice) -> String {
+    let mut outcomes: Vec<(f64, String)> = Vec::new();
+    for (chance, event) in lib.resolve_event(&choice.event) {
+        let desc = describe_event(lib, event);
+        if let Some(x) = outcomes.iter_mut().find(|(_, x)| *x == desc) {
+            x.0 += chance;
+        } else {
+            outcomes.push((chance, desc));
+        }
+    }
+    match outcomes.as_slice() {
+        [] => String::new(),
+        [(_, desc)] => format!("\nPredicted outcome: {desc}"),
+        _ => {
+            "\nPossible outcomes:".to_owned()
+                + &outcomes
+                    .iter()
+                    .map(|(chance, desc)| format!("\n- {:.0}% chance: {desc}", chance * 100.0))
+                    .collect::<String>()
+        }
+    }
+}
+
+/// Outcomes of the last choice box, so they're only predicted once per opened box
+#[derive(Default)]
+pub(crate) struct OutcomeCache(RefCell<Option<(OutcomeKey, Vec<String>)>>);
+
+/// Event name, main text and choice texts
+type OutcomeKey = (String, String, Vec<String>);
+
+fn predict(key: &OutcomeKey) -> Vec<String> {
+    let (name, main_text, texts) = key;
+    let texts: Vec<&str> = texts.iter().map(|x| x.as_str()).collect();
+    let lib = crate::library();
+    let Some(choices) = find_event(lib, name, main_text, &texts) else {
+        return vec![String::new(); texts.len()];
+    };
+    choices
+        .into_iter()
+        .map(|x| x.map(|x| describe_choice(lib, x)).unwrap_or_default())
+        .collect()
+}
+
+/// Predicted outcomes for each option of the open choice box, empty strings where unknown.
+pub(crate) fn choice_outcomes(app: &CApp, cache: &OutcomeCache) -> Vec<String> {
+    let Some(gui) = app.gui() else {
+        return Vec::new();
+    };
+    let c = &gui.choice_box;
+    let key = (
+        app.world()
+            .and_then(|x| x.last_location_event())
+            .map(|x| x.event_name.to_str().into_owned())
+            .unwrap_or_default(),
+        c.main_text.to_str().into_owned(),
+        c.choices
+            .iter()
+            .map(|x| x.text.to_str().into_owned())
+            .collect(),
+    );
+    let mut cache = cache.0.borrow_mut();
+    match &*cache {
+        Some((k, outcomes)) if *k == key => outcomes.clone(),
+        _ => {
+            let outcomes = predict(&key);
+            *cache = Some((key, outcomes.clone()));
+            outcomes
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{describe_blueprint_list, describe_choice, find_event};
+    use crate::TextLibrary;
+
+    #[test]
+    fn test_outcomes() {
+        let text = r#"
+            <FTL>
+            <event name="MERCHANT">
+                <text>A merchant hails you.</text>
+                <choice>
+                    <text>Attack them.</text>
+                    <event>
+                        <ship load="MERCHANT_SHIP" hostile="true"/>
+                    </event>
+                </choice>
+                <choice req="ENGI">
+                    <text>Trade.</text>
+                    <event load="TRADE_LIST"/>
+                </choice>
+                <choice>
+                    <text>Leave.</text>
+                    <event/>
+                </choice>
+            </event>
+            <eventList name="TRADE_LIST">
+                <event><item_modify><item type="scrap" min="10" max="20"/></item_modify></event>
+                <event><boarders min="2" max="2" class="mantis"/><damage amount="3"/></event>
+                <event><item_modify><item type="fuel" min="-3" max="-3"/></item_modify></event>
+                <event><item_modify><item type="scrap" min="10" max="20"/></item_modify></event>
+            </eventList>
+            <eventList name="START">
+                <event load="MERCHANT"/>
+            </eventList>
+            <ship name="MERCHANT_SHIP" auto_blueprint="SHIP_MERCHANT"/>
+            </FTL>
+        "#;
+        let mut lib = TextLibrary::default();
+        lib.add_events(quick_xml::de::from_str(text).unwrap());
+        let choices = find_event(
+            &lib,
+            "START",
+            "A merchant hails you.",
+            &["Attack them.", "(Engi) Trade.", "Leave."],
+        )
+        .unwrap();
+        let choices: Vec<_> = choices
+            .into_iter()
+            .map(|x| describe_choice(&lib, x.unwrap()))
+            .collect();
+        assert_eq!(
+            choices,
+            [
+                "\nPredicted outcome: a hostile ship (SHIP_MERCHANT) will attack you",
+                concat!(
+                    "\nPossible outcomes:",
+                    "\n- 50% chance: will get 10-20 scrap",
+                    "\n- 25% chance: 2 mantis intruders will board your ship, ship hull will take 3 damage",
+                    "\n- 25% chance: will lose 3 fuel",
+                ),
+                "\nPredicted outcome: nothing happens",
+            ]
+        );
+        // unknown name, search everything
+        let choices = find_event(&lib, "", "", &["Leave.", "Attack them."]).unwrap();
+        assert!(choices.iter().all(|x| x.is_some()));
+        assert!(find_event(&lib, "", "", &["Something else."]).is_none());
+    }
+
+    #[test]
+    fn test_event_loops() {
+        // every event leads to the next one and the last one back to the first
+        let events: String = (0..20)
+            .map(|i| {
+                let next = (i + 1) % 20;
+                format!(
+                    r#"<event name="LOOP_{i}"><text>Event {i}.</text>
+                    <choice><text>Continue {i}.</text><event load="LOOP_{next}"/></choice>
+                    <choice><text>Also continue {i}.</text><event load="LOOP_{next}"/></choice>
+                    <choice><text>Or continue {i}.</text><event load="LOOP_{next}"/></choice>
+                    </event>"#
+                )
+            })
+            .collect();
+        let mut lib = TextLibrary::default();
+        lib.add_events(quick_xml::de::from_str(&format!("<FTL>{events}</FTL>")).unwrap());
+        let choices = find_event(&lib, "LOOP_0", "Event 5.", &["Continue 5."]).unwrap();
+        assert_eq!(choices.len(), 1);
+        assert!(choices[0].is_some());
+        assert!(find_event(&lib, "LOOP_0", "", &["Continue 19."]).is_none());
+    }
+
+    #[test]
+    fn test_blueprint_lists() {
+        let blueprints = r#"
+            <FTL>
+            <augBlueprint name="AUG_A">
+                <title>Augment A</title><desc>A</desc><cost>30</cost><rarity>1</rarity><stackable>false</stackable>
+            </augBlueprint>
+            <augBlueprint name="AUG_B">
+                <title>Augment B</title><desc>B</desc><cost>50</cost><rarity>2</rarity><stackable>true</stackable>
+            </augBlueprint>
+            <blueprintList name="INNER">
+                <name>AUG_B</name>
+                <name>AUG_A</name>
+                <name>AUG_MISSING</name>
+            </blueprintList>
+            <blueprintList name="OUTER">
+                <name>AUG_A</name>
+                <name>INNER</name>
+            </blueprintList>
+            </FTL>
+        "#;
+        let events = r#"
+            <FTL>
+            <event name="GIFT"><text>A gift.</text><augment name="OUTER"/></event>
+            </FTL>
+        "#;
+        let mut lib = TextLibrary::default();
+        lib.add_blueprints(quick_xml::de::from_str(blueprints).unwrap());
+        lib.add_events(quick_xml::de::from_str(events).unwrap());
+        assert_eq!(lib.resolve_blueprint_list("OUTER"), ["AUG_A", "AUG_B"]);
+        assert_eq!(lib.resolve_blueprint_list("AUG_B"), ["AUG_B"]);
+        assert!(lib.resolve_blueprint_list("AUG_MISSING").is_empty());
+        assert_eq!(
+            describe_blueprint_list(&lib, "OUTER"),
+            "Augment A (30 scrap); Augment B (50 scrap)"
+        );
+        assert_eq!(
+            super::describe_event(&lib, lib.event("GIFT").unwrap()),
+            "will get an augment, one of: Augment A (30 scrap); Augment B (50 scrap)"
+        );
+    }
+}
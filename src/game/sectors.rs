@@ -0,0 +1,169 @@
+//! What a sector has in store, from its description in the game data
+use std::collections::BTreeMap;
+
+use crate::{xml, TextLibrary};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Beacon {
+    HostileShip,
+    Store,
+    Sun,
+    Asteroids,
+    Pulsar,
+    Nebula,
+    IonStorm,
+    Pds,
+    Other,
+}
+
+impl Beacon {
+    fn name(self, plural: bool) -> &'static str {
+        match (self, plural) {
+            (Self::HostileShip, false) => "hostile ship",
+            (Self::HostileShip, true) => "hostile ships",
+            (Self::Store, false) => "store",
+            (Self::Store, true) => "stores",
+            (Self::Sun, false) => "beacon near a sun",
+            (Self::Sun, true) => "beacons near a sun",
+            (Self::Asteroids, false) => "asteroid field",
+            (Self::Asteroids, true) => "asteroid fields",
+            (Self::Pulsar, false) => "pulsar",
+            (Self::Pulsar, true) => "pulsars",
+            (Self::Nebula, false) => "nebula beacon",
+            (Self::Nebula, true) => "nebula beacons",
+            (Self::IonStorm, false) => "ion storm",
+            (Self::IonStorm, true) => "ion storms",
+            (Self::Pds, false) => "planetary defense system",
+            (Self::Pds, true) => "planetary defense systems",
+            (Self::Other, false) => "other event",
+            (Self::Other, true) => "other events",
+        }
+    }
+}
+
+/// The most likely kind of beacon an event (or event list) turns into
+fn classify(lib: &TextLibrary, name: &str) -> Beacon {
+    let mut chances = BTreeMap::<Beacon, f64>::new();
+    for (chance, event) in lib.resolve_event_name(name) {
+        let kind = if event.ship.as_ref().is_some_and(|x| x.hostile) {
+            Beacon::HostileShip
+        } else if event.store.is_some() {
+            Beacon::Store
+        } else if let Some(env) = &event.environment {
+            match env.type_ {
+                xml::EnvironmentType::Sun => Beacon::Sun,
+                xml::EnvironmentType::Asteroid => Beacon::Asteroids,
+                xml::EnvironmentType::Pulsar => Beacon::Pulsar,
+                xml::EnvironmentType::Nebula => Beacon::Nebula,
+                xml::EnvironmentType::Storm => Beacon::IonStorm,
+                xml::EnvironmentType::Pds => Beacon::Pds,
+            }
+        } else {
+            Beacon::Other
+        };
+        *chances.entry(kind).or_default() += chance;
+    }
+    chances
+        .into_iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(Beacon::Other, |x| x.0)
+}
+
+/// How many beacons of each kind a sector has, e.g. "2-4 hostile ships"
+pub(crate) fn expected_beacons(
+    lib: &TextLibrary,
+    sector: &xml::SectorDescription,
+    level: u32,
+) -> Vec<String> {
+    let mut counts = BTreeMap::<Beacon, (u32, u32)>::new();
+    for count in sector.events.iter().chain(lib.event_counts(level)) {
+        let (min, max) = counts.entry(classify(lib, &count.name)).or_default();
+        *min += count.min;
+        *max += count.max;
+    }
+    counts
+        .into_iter()
+        .filter(|(_, (_, max))| *max > 0)
+        .map(|(kind, (min, max))| {
+            let name = kind.name(max != 1);
+            if min == max {
+                format!("{min} {name}")
+            } else {
+                format!("{min}-{max} {name}")
+            }
+        })
+        .collect()
+}
+
+/// Items that show up more often than usual in the sector's stores and rewards
+pub(crate) fn common_items(lib: &TextLibrary, sector: &xml::SectorDescription) -> Vec<String> {
+    let mut items: Vec<_> = sector
+        .rarity_list
+        .iter()
+        .flat_map(|x| x.blueprints.iter())
+        .filter(|x| (1..=2).contains(&x.rarity))
+        .collect();
+    items.sort_by_key(|x| x.rarity);
+    items
+        .into_iter()
+        .map(|x| lib.blueprint_name(&x.name).unwrap_or(&x.name).to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{common_items, expected_beacons};
+    use crate::TextLibrary;
+
+    #[test]
+    fn test_sectors() {
+        let events = r#"
+            <FTL>
+            <event name="PIRATE"><text>Pirate</text><ship load="SHIP" hostile="true"/></event>
+            <event name="TRADER"><text>Trader</text><ship load="SHIP"/></event>
+            <event name="STORE"><text>Store</text><store/></event>
+            <event name="SUN"><text>Sun</text><environment type="sun"/></event>
+            <event name="ROCKS"><text>Rocks</text><environment type="asteroid"/></event>
+            <eventList name="NEUTRAL">
+                <event load="PIRATE"/>
+                <event load="TRADER"/>
+                <event load="TRADER"/>
+            </eventList>
+            <eventCounts sector="1">
+                <event name="STORE" min="1" max="1"/>
+            </eventCounts>
+            </FTL>
+        "#;
+        let sectors = r#"
+            <FTL>
+            <sectorDescription name="TEST_SECTOR" minSector="0">
+                <event name="PIRATE" min="2" max="4"/>
+                <event name="NEUTRAL" min="1" max="2"/>
+                <event name="SUN" min="0" max="1"/>
+                <event name="ROCKS" min="2" max="2"/>
+                <event name="STORE" min="1" max="1"/>
+                <rarityList>
+                    <blueprint name="RARE" rarity="5"/>
+                    <blueprint name="COMMON" rarity="1"/>
+                </rarityList>
+            </sectorDescription>
+            </FTL>
+        "#;
+        let mut lib = TextLibrary::default();
+        lib.add_events(quick_xml::de::from_str(events).unwrap());
+        lib.add_sectors(quick_xml::de::from_str(sectors).unwrap());
+        let sector = lib.sector("TEST_SECTOR").unwrap();
+        assert_eq!(
+            expected_beacons(&lib, sector, 0),
+            [
+                "2-4 hostile ships",
+                "1 store",
+                "0-1 beacon near a sun",
+                "2 asteroid fields",
+                "1-2 other events"
+            ]
+        );
+        assert_eq!(expected_beacons(&lib, sector, 1)[1], "2 stores");
+        assert_eq!(common_items(&lib, sector), ["COMMON"]);
+    }
+}
@@ -32,6 +32,7 @@ pub const SKILL_SUFFOCATION_RES: &str =
     "Suffocation resistance, with 100 being 0 damage from suffocation";
 pub const SKILL_LOCKDOWN: &str = "Lockdown power, activate with the `lockdown` action";
 
-pub fn text(s: &str) -> &'static str {
-    crate::library().text(s).unwrap()
+/// Game text by its id, or the id itself if the game data couldn't be loaded
+pub fn text(s: &'static str) -> &'static str {
+    crate::library().text(s).unwrap_or(s)
 }
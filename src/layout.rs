@@ -0,0 +1,201 @@
+//! Ship layouts, the room grid from `data/<layout>.txt`
+use std::io;
+
+use crate::xml;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Room {
+    pub id: u32,
+    /// grid position of the top left corner
+    pub x: i32,
+    pub y: i32,
+    /// size in grid cells
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Room {
+    /// Which side of `self` the other room is on, if they share a wall
+    pub fn side_of(&self, other: &Room) -> Option<xml::Direction> {
+        let overlaps =
+            |a: i32, a_len: i32, b: i32, b_len: i32| a.max(b) < (a + a_len).min(b + b_len);
+        if overlaps(self.y, self.h, other.y, other.h) {
+            if other.x == self.x + self.w {
+                return Some(xml::Direction::Right);
+            } else if other.x + other.w == self.x {
+                return Some(xml::Direction::Left);
+            }
+        }
+        if overlaps(self.x, self.w, other.x, other.w) {
+            if other.y == self.y + self.h {
+                return Some(xml::Direction::Down);
+            } else if other.y + other.h == self.y {
+                return Some(xml::Direction::Up);
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Door {
+    pub x: i32,
+    pub y: i32,
+    /// -1 for airlocks
+    pub room1: i32,
+    pub room2: i32,
+    pub vertical: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ellipse {
+    pub w: i32,
+    pub h: i32,
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Layout {
+    /// offset of the grid in cells
+    pub x_offset: i32,
+    pub y_offset: i32,
+    /// offset of the grid in pixels
+    pub horizontal: i32,
+    pub vertical: i32,
+    pub ellipse: Option<Ellipse>,
+    pub rooms: Vec<Room>,
+    pub doors: Vec<Door>,
+}
+
+fn num<'a>(tokens: &mut impl Iterator<Item = &'a str>, what: &str) -> io::Result<i32> {
+    let x = tokens.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("expected {what}, got end of file"),
+        )
+    })?;
+    x.parse().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected {what}, got {x:?}"),
+        )
+    })
+}
+
+impl Layout {
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut tokens = text.split_whitespace();
+        let tokens = &mut tokens;
+        let mut ret = Self::default();
+        while let Some(keyword) = tokens.next() {
+            match keyword {
+                "X_OFFSET" => ret.x_offset = num(tokens, "x offset")?,
+                "Y_OFFSET" => ret.y_offset = num(tokens, "y offset")?,
+                "HORIZONTAL" => ret.horizontal = num(tokens, "horizontal offset")?,
+                "VERTICAL" => ret.vertical = num(tokens, "vertical offset")?,
+                "ELLIPSE" => {
+                    ret.ellipse = Some(Ellipse {
+                        w: num(tokens, "ellipse width")?,
+                        h: num(tokens, "ellipse height")?,
+                        x: num(tokens, "ellipse x")?,
+                        y: num(tokens, "ellipse y")?,
+                    })
+                }
+                "ROOM" => {
+                    let id = num(tokens, "room id")?;
+                    ret.rooms.push(Room {
+                        id: u32::try_from(id).map_err(|_| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("invalid room id {id}"),
+                            )
+                        })?,
+                        x: num(tokens, "room x")?,
+                        y: num(tokens, "room y")?,
+                        w: num(tokens, "room width")?,
+                        h: num(tokens, "room height")?,
+                    });
+                }
+                "DOOR" => ret.doors.push(Door {
+                    x: num(tokens, "door x")?,
+                    y: num(tokens, "door y")?,
+                    room1: num(tokens, "door room")?,
+                    room2: num(tokens, "door room")?,
+                    vertical: num(tokens, "door orientation")? != 0,
+                }),
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unknown layout keyword {keyword:?}"),
+                    ))
+                }
+            }
+        }
+        if ret.rooms.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "layout has no rooms",
+            ));
+        }
+        Ok(ret)
+    }
+    pub fn room(&self, id: u32) -> Option<&Room> {
+        self.rooms.iter().find(|x| x.id == id)
+    }
+    /// Rooms sharing a wall with the given room, and which side of it they're on
+    pub fn neighbors(&self, id: u32) -> Vec<(u32, xml::Direction)> {
+        let Some(room) = self.room(id) else {
+            return Vec::new();
+        };
+        self.rooms
+            .iter()
+            .filter_map(|x| Some((x.id, room.side_of(x)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Door, Layout};
+    use crate::xml::Direction;
+
+    #[test]
+    fn test_layout() {
+        // +---+---+
+        // | 0 | 1 |
+        // +---+---+---+
+        //     |   2   |
+        //     +-------+
+        let text = "X_OFFSET\n4\nY_OFFSET\n2\nHORIZONTAL\n0\nVERTICAL\n-10\n\
+            ELLIPSE\n230\n135\n0\n0\n\
+            ROOM\n0\n0\n0\n1\n1\nROOM\n1\n1\n0\n1\n1\nROOM\n2\n1\n1\n2\n1\n\
+            DOOR\n1\n0\n0\n1\n1\nDOOR\n1\n1\n1\n2\n0\n";
+        let layout = Layout::parse(text).unwrap();
+        assert_eq!((layout.x_offset, layout.y_offset), (4, 2));
+        assert_eq!(layout.vertical, -10);
+        assert_eq!(layout.rooms.len(), 3);
+        assert_eq!(layout.room(2).unwrap().w, 2);
+        assert_eq!(
+            layout.doors[0],
+            Door {
+                x: 1,
+                y: 0,
+                room1: 0,
+                room2: 1,
+                vertical: true
+            }
+        );
+        assert_eq!(layout.neighbors(0), [(1, Direction::Right)]);
+        assert_eq!(
+            layout.neighbors(1),
+            [(0, Direction::Left), (2, Direction::Down)]
+        );
+        assert_eq!(layout.neighbors(2), [(1, Direction::Up)]);
+        assert!(layout.neighbors(3).is_empty());
+
+        assert!(Layout::parse("ROOM\n0\n0\n0\n1").is_err());
+        assert!(Layout::parse("FLOOR\n0").is_err());
+        assert!(Layout::parse("").is_err());
+    }
+}
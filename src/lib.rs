@@ -1,7 +1,10 @@
 #![allow(clippy::missing_safety_doc)]
 use std::{
+    borrow::Cow,
     collections::HashMap,
     ffi::{c_int, c_uint, c_void, CStr},
+    io,
+    path::PathBuf,
     ptr,
     sync::OnceLock,
 };
@@ -15,12 +18,17 @@ use ctor::ctor;
 use game::{activated, deactivate};
 
 pub mod bindings;
+mod blueprints;
+pub mod builds;
 pub mod cross;
 pub mod game;
+pub mod layout;
 mod logger;
 pub mod pak;
+pub mod slipstream;
 #[cfg(target_os = "windows")]
 pub mod steam_shim;
+pub mod vfs;
 pub mod xml;
 
 #[allow(clippy::large_enum_variant)]
@@ -34,74 +42,249 @@ enum Blueprint {
     Item(xml::ItemBlueprint),
 }
 
+#[derive(Default)]
 struct TextLibrary {
     text: HashMap<String, String>,
+    /// language -> key -> text, keys missing here fall back to the English `text`
+    translations: HashMap<String, HashMap<String, String>>,
     blueprints: HashMap<String, Blueprint>,
+    blueprint_lists: HashMap<String, xml::BlueprintList>,
+    events: HashMap<String, xml::Event>,
+    event_lists: HashMap<String, xml::EventList>,
+    text_lists: HashMap<String, xml::TextList>,
+    ships: HashMap<String, xml::Ship>,
+    layouts: HashMap<String, layout::Layout>,
+    achievements: Vec<xml::Achievement>,
+    sectors: HashMap<String, xml::SectorDescription>,
+    /// sector level -> events that are added to every sector at that level
+    event_counts: HashMap<u32, Vec<xml::EventCount>>,
+    /// problems found in the game data while loading it
+    diagnostics: Vec<xml::lenient::Diagnostic>,
+}
+
+/// Where the game data is loaded from
+#[derive(Clone, Debug, Default)]
+pub struct DataPaths {
+    /// the directory with `ftl.dat` (or `data.dat` and `resource.dat`)
+    pub game_dir: PathBuf,
+    /// paks loaded on top of the game's own
+    pub extra_paks: Vec<PathBuf>,
+    /// slipstream mods applied on top of everything else
+    pub mods: Vec<PathBuf>,
+}
+
+impl DataPaths {
+    /// Paths from `NEURO_FTL_DATA_DIR` (the game executable's directory by default),
+    /// `NEURO_FTL_PAKS` and `NEURO_FTL_MODS`
+    pub fn from_env() -> io::Result<Self> {
+        let list = |var| {
+            std::env::var_os(var)
+                .map(|x| std::env::split_paths(&x).collect::<Vec<_>>())
+                .unwrap_or_default()
+        };
+        let game_dir = match std::env::var_os("NEURO_FTL_DATA_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => {
+                let exe = std::env::current_exe()?;
+                exe.parent()
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::NotFound,
+                            format!("{} has no parent directory", exe.display()),
+                        )
+                    })?
+                    .to_owned()
+            }
+        };
+        Ok(Self {
+            game_dir,
+            extra_paks: list("NEURO_FTL_PAKS"),
+            mods: list("NEURO_FTL_MODS"),
+        })
+    }
 }
 
 impl TextLibrary {
-    pub fn new() -> Self {
-        let exe = std::env::current_exe().unwrap();
-        let dat_path = exe.parent().unwrap().join("ftl.dat");
-        let mut file = pak::Data::open(dat_path).unwrap();
-        let mut blueprints = HashMap::new();
-        let mut text = HashMap::new();
-        for k in file.file_list() {
-            if k.starts_with("data/text_") {
-                let contents = file.read(&k).unwrap();
-                let contents = std::str::from_utf8(&contents)
-                    .unwrap()
-                    .replace("</event>-", "</event>")
-                    .replace("/>.\r", "/>\r")
-                    .replace(">1.f<", ">1.0<");
-                let data: xml::XmlText = quick_xml::de::from_str(&contents).unwrap();
-                for t in data.text {
-                    if t.language.is_none() {
-                        text.insert(t.name, t.value);
-                    }
-                }
-            } else if k.contains("luepr") {
-                let contents = file.read(&k).unwrap();
-                let contents = std::str::from_utf8(&contents)
-                    .unwrap()
-                    .replace("</event>-", "</event>")
-                    .replace("/>.\r", "/>\r")
-                    .replace(">1.f<", ">1.0<");
-                let data: xml::XmlBlueprints = quick_xml::de::from_str(&contents).unwrap();
-                let xml::XmlBlueprints {
-                    blueprint_lists: _,
-                    aug_blueprints,
-                    crew_blueprints,
-                    item_blueprints,
-                    ship_blueprints,
-                    drone_blueprints,
-                    system_blueprints,
-                    weapon_blueprints,
-                } = data;
-                for b in aug_blueprints {
-                    blueprints.insert(b.name.clone(), Blueprint::Augment(b));
+    pub fn load(paths: &DataPaths) -> io::Result<Self> {
+        let dat_path = &paths.game_dir;
+        let mut file = vfs::Vfs::open_game_dir(dat_path, &paths.extra_paks).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("failed to open game data in {}: {err}", dat_path.display()),
+            )
+        })?;
+        for path in &paths.mods {
+            match slipstream::Mod::open(path) {
+                Ok(m) => {
+                    log::info!("applying mod {}", path.display());
+                    file.push_mod(m);
                 }
-                for b in crew_blueprints {
-                    blueprints.insert(b.name.clone(), Blueprint::Crew(b));
-                }
-                for b in item_blueprints {
-                    blueprints.insert(b.name.clone(), Blueprint::Item(b));
-                }
-                for b in ship_blueprints {
-                    blueprints.insert(b.name.clone(), Blueprint::Ship(b));
+                Err(err) => log::error!("failed to open mod {}: {err}", path.display()),
+            }
+        }
+        let mut ret = Self::default();
+        let mut damaged = false;
+        let mut files: Vec<String> = file.glob("data/*.xml").map(str::to_owned).collect();
+        // the game loads these after the other event files
+        files.sort_by_key(|k| k.contains("Overwrite"));
+        for k in files {
+            let contents = match file.verify_entry(&k).and_then(|()| file.read(&k)) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    log::warn!("{k} is damaged, skipping it: {err}");
+                    damaged = true;
+                    continue;
                 }
-                for b in drone_blueprints {
-                    blueprints.insert(b.name.clone(), Blueprint::Drone(b));
+            };
+            let doc = xml::lenient::preprocess(&k, &contents, &mut ret.diagnostics);
+            if k.starts_with("data/text_") {
+                let data = doc.parse(&mut ret.diagnostics);
+                ret.add_text(data);
+            } else if k.contains("luepr") {
+                let data = doc.parse(&mut ret.diagnostics);
+                ret.add_blueprints(data);
+            } else {
+                match doc.first_element() {
+                    Some("sectorType" | "sectorDescription") => {
+                        let data = doc.parse(&mut ret.diagnostics);
+                        ret.add_sectors(data);
+                    }
+                    Some("achievement") => {
+                        let data: xml::XmlAchievements = doc.parse(&mut ret.diagnostics);
+                        ret.achievements.extend(data.achievements);
+                    }
+                    Some("event" | "eventList" | "textList" | "ship") => {
+                        let data = doc.parse(&mut ret.diagnostics);
+                        ret.add_events(data);
+                    }
+                    _ => {}
                 }
-                for b in system_blueprints {
-                    blueprints.insert(b.name.clone(), Blueprint::System(b));
+            }
+        }
+        let files: Vec<String> = file.glob("data/*.txt").map(str::to_owned).collect();
+        for k in files {
+            let contents = match file.verify_entry(&k).and_then(|()| file.read(&k)) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    log::warn!("{k} is damaged, skipping it: {err}");
+                    damaged = true;
+                    continue;
                 }
-                for b in weapon_blueprints {
-                    blueprints.insert(b.name.clone(), Blueprint::Weapon(b));
+            };
+            let layout = match layout::Layout::parse(&String::from_utf8_lossy(&contents)) {
+                Ok(layout) => layout,
+                Err(err) => {
+                    log::debug!("{k} is not a ship layout: {err}");
+                    continue;
                 }
+            };
+            let name = &k["data/".len()..k.len() - ".txt".len()];
+            ret.layouts.insert(name.to_owned(), layout);
+        }
+        for d in &ret.diagnostics {
+            log::log!(d.level, "{d}");
+        }
+        if damaged {
+            log::warn!(
+                "game data in {} is damaged, some names and descriptions will be missing. Try verifying the game files",
+                dat_path.display()
+            );
+        }
+        Ok(ret)
+    }
+    fn add_text(&mut self, data: xml::XmlText) {
+        for t in data.text {
+            if let Some(lang) = t.language {
+                self.translations
+                    .entry(lang)
+                    .or_default()
+                    .insert(t.name, t.value);
+            } else {
+                self.text.insert(t.name, t.value);
             }
         }
-        TextLibrary { text, blueprints }
+    }
+    fn add_blueprints(&mut self, data: xml::XmlBlueprints) {
+        let xml::XmlBlueprints {
+            blueprint_lists,
+            aug_blueprints,
+            crew_blueprints,
+            item_blueprints,
+            ship_blueprints,
+            drone_blueprints,
+            system_blueprints,
+            weapon_blueprints,
+        } = data;
+        for l in blueprint_lists {
+            self.blueprint_lists.insert(l.name.clone(), l);
+        }
+        let blueprints = &mut self.blueprints;
+        for b in aug_blueprints {
+            blueprints.insert(b.name.clone(), Blueprint::Augment(b));
+        }
+        for b in crew_blueprints {
+            blueprints.insert(b.name.clone(), Blueprint::Crew(b));
+        }
+        for b in item_blueprints {
+            blueprints.insert(b.name.clone(), Blueprint::Item(b));
+        }
+        for b in ship_blueprints {
+            blueprints.insert(b.name.clone(), Blueprint::Ship(b));
+        }
+        for b in drone_blueprints {
+            blueprints.insert(b.name.clone(), Blueprint::Drone(b));
+        }
+        for b in system_blueprints {
+            blueprints.insert(b.name.clone(), Blueprint::System(b));
+        }
+        for b in weapon_blueprints {
+            blueprints.insert(b.name.clone(), Blueprint::Weapon(b));
+        }
+    }
+    fn add_events(&mut self, data: xml::XmlEvents) {
+        let xml::XmlEvents {
+            events,
+            event_lists,
+            text_lists,
+            ships,
+            event_counts,
+        } = data;
+        for e in events {
+            if let Some(name) = e.name.clone() {
+                self.events.insert(name, e);
+            }
+        }
+        for l in event_lists {
+            self.event_lists.insert(l.name.clone(), l);
+        }
+        for l in text_lists {
+            self.text_lists.insert(l.name.clone(), l);
+        }
+        for s in ships {
+            self.ships.insert(s.name.clone(), s);
+        }
+        for c in event_counts {
+            self.event_counts
+                .entry(c.sector)
+                .or_default()
+                .extend(c.events);
+        }
+    }
+    fn add_sectors(&mut self, data: xml::XmlSectors) {
+        let xml::XmlSectors {
+            sector_types: _,
+            sector_descriptions,
+            event_counts,
+        } = data;
+        for s in sector_descriptions {
+            self.sectors.insert(s.name.clone(), s);
+        }
+        for c in event_counts {
+            self.event_counts
+                .entry(c.sector)
+                .or_default()
+                .extend(c.events);
+        }
     }
     fn text_str<'a>(&'a self, s: &'a xml::TextString) -> Option<&'a str> {
         if let Some(ret) = &s.contents {
@@ -110,6 +293,14 @@ impl TextLibrary {
             Some(ret)
         } else if let Some(ret) = s.load.as_ref().and_then(|s| self.text(s)) {
             Some(ret)
+        } else if let Some(ret) = s
+            .load
+            .as_ref()
+            .and_then(|s| self.text_list(s))
+            .and_then(|l| l.text.first())
+            .and_then(|s| self.text_str(s))
+        {
+            Some(ret)
         } else {
             None
         }
@@ -129,13 +320,157 @@ impl TextLibrary {
             None
         }
     }
+    pub fn blueprint_list(&self, name: &str) -> Option<&xml::BlueprintList> {
+        self.blueprint_lists.get(name)
+    }
+    /// Every blueprint a blueprint list can pick from, following nested lists. A blueprint's own
+    /// name resolves to itself.
+    pub fn resolve_blueprint_list<'a>(&'a self, name: &'a str) -> Vec<&'a str> {
+        let mut ret = Vec::new();
+        self.resolve_blueprint_list_into(name, 16, &mut ret);
+        ret
+    }
+    fn resolve_blueprint_list_into<'a>(
+        &'a self,
+        name: &'a str,
+        depth: usize,
+        out: &mut Vec<&'a str>,
+    ) {
+        if let Some(list) = self.blueprint_lists.get(name) {
+            if depth == 0 {
+                log::warn!("blueprint list {name} is nested too deep");
+                return;
+            }
+            for x in &list.names {
+                self.resolve_blueprint_list_into(x, depth - 1, out);
+            }
+        } else if self.blueprints.contains_key(name) {
+            if !out.contains(&name) {
+                out.push(name);
+            }
+        } else {
+            log::debug!("unknown blueprint {name}");
+        }
+    }
+    /// Text in the game's current language, or in English if it isn't translated.
     pub fn text(&self, text: &str) -> Option<&str> {
-        self.text.get(text).map(|x| x.as_str())
+        self.text_in(language().as_deref(), text)
+    }
+    fn text_in(&self, language: Option<&str>, text: &str) -> Option<&str> {
+        language
+            .and_then(|x| self.translations.get(x))
+            .and_then(|x| x.get(text))
+            .or_else(|| self.text.get(text))
+            .map(|x| x.as_str())
+    }
+    pub fn event(&self, name: &str) -> Option<&xml::Event> {
+        self.events.get(name)
+    }
+    pub fn event_list(&self, name: &str) -> Option<&xml::EventList> {
+        self.event_lists.get(name)
+    }
+    /// A sector description by its `name`, which the game stores as the sector's type
+    pub fn sector(&self, name: &str) -> Option<&xml::SectorDescription> {
+        self.sectors.get(name)
+    }
+    /// Events every sector at the given level (starting from 0) gets in addition to its own
+    pub fn event_counts(&self, level: u32) -> &[xml::EventCount] {
+        self.event_counts.get(&level).map_or(&[], |x| x.as_slice())
+    }
+    /// Achievement definitions in the order the game lists them
+    pub fn achievements(&self) -> &[xml::Achievement] {
+        &self.achievements
+    }
+    /// A ship layout by the name used in `shipBlueprint`'s `layout` attribute
+    pub fn layout(&self, name: &str) -> Option<&layout::Layout> {
+        self.layouts.get(name)
+    }
+    pub fn text_list(&self, name: &str) -> Option<&xml::TextList> {
+        self.text_lists.get(name)
+    }
+    pub fn ship<'a>(&'a self, ship: &'a xml::ShipRef) -> Option<&'a xml::Ship> {
+        ship.ship
+            .as_deref()
+            .or_else(|| ship.load.as_ref().and_then(|x| self.ships.get(x)))
+    }
+    /// Follow an event's `load` references. Returns every event it may turn into along with its
+    /// probability, an event list picks one of its events at random.
+    pub fn resolve_event<'a>(&'a self, event: &'a xml::Event) -> Vec<(f64, &'a xml::Event)> {
+        let mut ret = Vec::new();
+        self.resolve_event_into(event, 1.0, 16, &mut ret);
+        ret
+    }
+    /// Resolve an event or event list by name, see [`Self::resolve_event`].
+    pub fn resolve_event_name(&self, name: &str) -> Vec<(f64, &xml::Event)> {
+        let mut ret = Vec::new();
+        self.resolve_name_into(name, 1.0, 16, &mut ret);
+        ret
+    }
+    fn resolve_event_into<'a>(
+        &'a self,
+        event: &'a xml::Event,
+        chance: f64,
+        depth: usize,
+        out: &mut Vec<(f64, &'a xml::Event)>,
+    ) {
+        let Some(load) = &event.load else {
+            out.push((chance, event));
+            return;
+        };
+        if depth == 0 {
+            log::warn!("event {load} is nested too deep");
+            return;
+        }
+        self.resolve_name_into(load, chance, depth - 1, out);
+    }
+    fn resolve_name_into<'a>(
+        &'a self,
+        name: &str,
+        chance: f64,
+        depth: usize,
+        out: &mut Vec<(f64, &'a xml::Event)>,
+    ) {
+        if let Some(event) = self.event(name) {
+            self.resolve_event_into(event, chance, depth, out);
+        } else if let Some(list) = self.event_list(name) {
+            let chance = chance / list.events.len() as f64;
+            for event in &list.events {
+                self.resolve_event_into(event, chance, depth, out);
+            }
+        } else {
+            log::debug!("unknown event {name}");
+        }
     }
 }
 
+/// The game data, or an empty library if it couldn't be loaded. In that case names are taken from
+/// the game's memory where possible, and the rest of the mod keeps working without them.
 fn library() -> &'static TextLibrary {
-    TEXT.get_or_init(TextLibrary::new)
+    TEXT.get_or_init(|| {
+        DataPaths::from_env()
+            .and_then(|paths| TextLibrary::load(&paths))
+            .unwrap_or_else(|err| {
+                log::error!(
+                    "failed to load game data, names and descriptions will be missing: {err}"
+                );
+                TextLibrary::default()
+            })
+    })
+}
+
+/// The language picked in the game's language chooser, such as `de`. `None` for English or if the
+/// settings haven't been loaded yet.
+fn language() -> Option<Cow<'static, str>> {
+    let settings = unsafe { SETTINGS.0.as_ref() }?;
+    if settings.language.data.is_null() {
+        return None;
+    }
+    let ret = settings.language.to_str();
+    (!ret.is_empty() && ret != "en").then_some(ret)
+}
+
+fn achievement_tracker() -> &'static AchievementTracker {
+    unsafe { &*ACHIEVEMENTS.0 }
 }
 
 fn keeper() -> &'static ScoreKeeper {
@@ -205,7 +540,69 @@ static mut SET_TAB: cross::Fn2<0x517680, 0x585BE0, *mut TabbedWindow, c_uint, ()
 static mut SWITCH_SHIP: cross::Fn3<0x4EFA80, 0x54FD00, *mut ShipBuilder, c_int, c_int, ()> =
     cross::Fn3::new();
 
+pub(crate) const GEN_INPUT_EVENTS_SIG: cross::Signature = cross::Signature::new(
+    "CApp::GenInputEvents",
+    "57 8D 7C 24 08 83 E4 F0",
+    "55 53 48 89 FB 48 83 EC",
+);
+
+/// Pick the offsets for this build of the game, returns `false` if it's an unknown build and the
+/// usual offsets don't look right either
+unsafe fn select_build(base: *mut c_void) -> bool {
+    let Some(fingerprint) = builds::Fingerprint::of_image(base) else {
+        log::error!("couldn't find the executable's code to fingerprint it");
+        return false;
+    };
+    log::info!("executable fingerprint: {fingerprint}");
+    let table = builds::BuildTable::load();
+    let build = table.identify(fingerprint);
+    if let Some(build) = build {
+        log::info!("detected build {}", build.name);
+        cross::set_offsets(&build.offsets);
+    }
+    // quick sanity check, if the main loop can't be found this is a different build of the game
+    // and none of the other offsets can be trusted either
+    let offset = cross::relocate(cross::Hook1::<0x402AA0, 0x41C490, *mut CApp, ()>::OFFSET);
+    let gen_input_events = cross::resolve(base, offset, &GEN_INPUT_EVENTS_SIG);
+    match gen_input_events.resolution {
+        cross::Resolution::Offset => {}
+        cross::Resolution::Signature => log::warn!(
+            "{} is at {:x} instead of {offset:x}, functions without a signature may be wrong too",
+            GEN_INPUT_EVENTS_SIG.name,
+            gen_input_events.offset
+        ),
+        cross::Resolution::NotFound | cross::Resolution::Ambiguous => {
+            log::error!(
+                "{} ({} not found), not hooking anything. If this is a different version of FTL, \
+                add a build with `fingerprint {fingerprint}` and its offsets to a table in \
+                NEURO_FTL_BUILDS",
+                match build {
+                    Some(build) => format!("the offsets for {} are wrong", build.name),
+                    None => "unknown build of the game".to_owned(),
+                },
+                GEN_INPUT_EVENTS_SIG.name
+            );
+            return false;
+        }
+    }
+    if build.is_none() && gen_input_events.resolution == cross::Resolution::Offset {
+        log::warn!(
+            "unknown executable, but its code matches {} so using its offsets. Add \
+            `fingerprint {fingerprint}` to src/builds to recognize it",
+            table.reference().map_or("the bindings", |x| &x.name)
+        );
+    }
+    true
+}
+
 unsafe fn hook(base: *mut c_void) {
+    if !select_build(base) {
+        return;
+    }
+    if std::env::var_os("NEURO_FTL_CHECK_POINTERS").is_some() {
+        log::info!("checking pointers before dereferencing them");
+        bindings::set_check_pointers(true);
+    }
     ACHIEVEMENTS.init(base);
     KEEPER.init(base);
     SETTINGS.init(base);
@@ -220,27 +617,6 @@ unsafe fn hook(base: *mut c_void) {
     SET_TAB.init(base);
     SWITCH_SHIP.init(base);
 
-    // quick sanity check
-    {
-        let mut gen_input_events = cross::Ptr::<0x402AA0, 0x41C490, c_void>::new();
-        gen_input_events.init(base);
-        let gen_input_events = gen_input_events.0;
-        if std::slice::from_raw_parts(gen_input_events as *const u8, 8) != {
-            #[cfg(all(target_os = "linux", target_pointer_width = "64"))]
-            {
-                b"USH\x89\xfbH\x83\xec"
-            }
-            #[cfg(target_os = "windows")]
-            b"W\x8d|$\x08\x83\xe4\xf0"
-        } {
-            log::error!(
-                "mismatch: {:?}",
-                std::slice::from_raw_parts(gen_input_events as *const u8, 8)
-            );
-            return;
-        }
-    }
-
     #[cfg(target_os = "linux")]
     {
         static mut CRIT_ERR_HDLR: cross::Hook3<
@@ -264,12 +640,12 @@ unsafe fn hook(base: *mut c_void) {
                 }
             }
         }
-        CRIT_ERR_HDLR.init(base, crit_err_hdlr_hook);
+        CRIT_ERR_HDLR.init(base, "crit_err_hdlr", crit_err_hdlr_hook);
     }
 
     #[cfg(target_os = "windows")]
     {
-        static mut EXC_FILTER: OnceLock<
+        static EXC_FILTER: OnceLock<
             retour::GenericDetour<unsafe extern "system" fn(exceptioninfo: *const c_void) -> i32>,
         > = OnceLock::new();
         unsafe extern "system" fn exc_filter_hook(exceptioninfo: *const c_void) -> i32 {
@@ -284,21 +660,20 @@ unsafe fn hook(base: *mut c_void) {
                 EXC_FILTER.get().unwrap().call(exceptioninfo)
             }
         }
-        EXC_FILTER.get_or_init(|| {
-            let addr = base.byte_add(0x4B5A00 - 0x400000);
-            let func = std::mem::transmute::<
-                *mut c_void,
-                Option<unsafe extern "system" fn(exceptioninfo: *const c_void) -> i32>,
-            >(addr);
-            let detour = retour::GenericDetour::new(func.unwrap(), exc_filter_hook)
-                .map_err(|err| format!("failed to hook 0x4B5A00: {err}"))
-                .unwrap();
-            detour
-                .enable()
-                .map_err(|err| format!("failed to enable hook 0x4B5A00: {err}"))
-                .unwrap();
-            detour
-        });
+        let offset = cross::relocate(0x4B5A00 - cross::IMAGE_BASE);
+        let addr = base.byte_add(offset);
+        let func = std::mem::transmute::<
+            *mut c_void,
+            Option<unsafe extern "system" fn(exceptioninfo: *const c_void) -> i32>,
+        >(addr);
+        cross::install(
+            &EXC_FILTER,
+            "exception filter",
+            base,
+            offset,
+            func.unwrap(),
+            exc_filter_hook,
+        );
     }
 
     static mut GEN_INPUT_EVENTS: cross::Hook1<0x402AA0, 0x41C490, *mut CApp, ()> =
@@ -309,7 +684,7 @@ unsafe fn hook(base: *mut c_void) {
             game::loop_hook(app);
         }
     }
-    GEN_INPUT_EVENTS.init(base, gen_input_events_hook);
+    GEN_INPUT_EVENTS.init_with(base, &GEN_INPUT_EVENTS_SIG, gen_input_events_hook);
 
     static mut PROJECTILE_INIT: cross::Hook2<
         0x45A430,
@@ -324,10 +699,10 @@ unsafe fn hook(base: *mut c_void) {
             game::projectile_post_init(proj, bp);
         }
     }
-    PROJECTILE_INIT.init(base, projectile_init_hook);
+    PROJECTILE_INIT.init(base, "Projectile::Initialize", projectile_init_hook);
 
     macro_rules! hook_proj_dtors {
-        ($(($a:expr, $b:expr, $c:expr, $d:expr),)+) => {
+        ($(($name:literal, $a:expr, $b:expr, $c:expr, $d:expr),)+) => {
             $({
                 static mut PROJECTILE_DTOR: cross::Hook1<
                     $a,
@@ -341,7 +716,7 @@ unsafe fn hook(base: *mut c_void) {
                         PROJECTILE_DTOR.call(proj);
                     }
                 }
-                PROJECTILE_DTOR.init(base, projectile_dtor_hook);
+                PROJECTILE_DTOR.init(base, $name, projectile_dtor_hook);
                 static mut PROJECTILE_DTOR2: cross::Hook1<
                     $b,
                     $d,
@@ -355,36 +730,132 @@ unsafe fn hook(base: *mut c_void) {
                         PROJECTILE_DTOR2.call(proj);
                     }
                 }
-                PROJECTILE_DTOR2.init(base, projectile_dtor_hook2);
+                PROJECTILE_DTOR2.init(base, concat!($name, " (base1)"), projectile_dtor_hook2);
             })+
         };
     }
     hook_proj_dtors!(
-        // format: windows, windows+8, linux, linux+8
+        // format: name, windows, windows+8, linux, linux+8
         // Projectile
-        (0x7562A0, 0x85D550, 0x44C1C0, 0x44C6D0),
-        (0x755FF0, 0x85D290, 0x44CC00, 0x44D120),
+        (
+            "Projectile::~Projectile #1",
+            0x7562A0,
+            0x85D550,
+            0x44C1C0,
+            0x44C6D0
+        ),
+        (
+            "Projectile::~Projectile #2",
+            0x755FF0,
+            0x85D290,
+            0x44CC00,
+            0x44D120
+        ),
         // BeamWeapon
-        (0x7543E0, 0x85C6D0, 0x431220, 0x431CB0),
-        (0x753DF0, 0x85C0E0, 0x430770, 0x431210),
+        (
+            "BeamWeapon::~BeamWeapon #1",
+            0x7543E0,
+            0x85C6D0,
+            0x431220,
+            0x431CB0
+        ),
+        (
+            "BeamWeapon::~BeamWeapon #2",
+            0x753DF0,
+            0x85C0E0,
+            0x430770,
+            0x431210
+        ),
         // BombProjectile
-        (0x450D90, 0x4507E0, 0x433C20, 0x434160),
-        (0x450AB0, 0x450510, 0x434170, 0x4346C0),
+        (
+            "BombProjectile::~BombProjectile #1",
+            0x450D90,
+            0x4507E0,
+            0x433C20,
+            0x434160
+        ),
+        (
+            "BombProjectile::~BombProjectile #2",
+            0x450AB0,
+            0x450510,
+            0x434170,
+            0x4346C0
+        ),
         // Missile
-        (0x76AC60, 0x85DAC0, 0x4453C0, 0x4458D0),
-        (0x76A9B0, 0x85D800, 0x4458E0, 0x445E00),
+        (
+            "Missile::~Missile #1",
+            0x76AC60,
+            0x85DAC0,
+            0x4453C0,
+            0x4458D0
+        ),
+        (
+            "Missile::~Missile #2",
+            0x76A9B0,
+            0x85D800,
+            0x4458E0,
+            0x445E00
+        ),
         // LaserBlast
-        (0x755BE0, 0x85CF70, 0x44C6E0, 0x44CBF0),
-        (0x755930, 0x85CCB0, 0x44E5D0, 0x44EAF0),
+        (
+            "LaserBlast::~LaserBlast #1",
+            0x755BE0,
+            0x85CF70,
+            0x44C6E0,
+            0x44CBF0
+        ),
+        (
+            "LaserBlast::~LaserBlast #2",
+            0x755930,
+            0x85CCB0,
+            0x44E5D0,
+            0x44EAF0
+        ),
         // Asteroid
-        (0x76B9C0, 0x85E7B0, 0x44D130, 0x44D640),
-        (0x76B710, 0x85E4F0, 0x44DB70, 0x44E090),
+        (
+            "Asteroid::~Asteroid #1",
+            0x76B9C0,
+            0x85E7B0,
+            0x44D130,
+            0x44D640
+        ),
+        (
+            "Asteroid::~Asteroid #2",
+            0x76B710,
+            0x85E4F0,
+            0x44DB70,
+            0x44E090
+        ),
         // CrewLaser
-        (0x7764A0, 0x85ED10, 0x44D650, 0x44DB60),
-        (0x7761F0, 0x85EA50, 0x44E0A0, 0x44E5C0),
+        (
+            "CrewLaser::~CrewLaser #1",
+            0x7764A0,
+            0x85ED10,
+            0x44D650,
+            0x44DB60
+        ),
+        (
+            "CrewLaser::~CrewLaser #2",
+            0x7761F0,
+            0x85EA50,
+            0x44E0A0,
+            0x44E5C0
+        ),
         // PDSFire
-        (0x76B340, 0x85E130, 0x44F240, 0x44F960),
-        (0x76AF70, 0x85DD60, 0x44EB00, 0x44F230),
+        (
+            "PDSFire::~PDSFire #1",
+            0x76B340,
+            0x85E130,
+            0x44F240,
+            0x44F960
+        ),
+        (
+            "PDSFire::~PDSFire #2",
+            0x76AF70,
+            0x85DD60,
+            0x44EB00,
+            0x44F230
+        ),
     );
 
     {
@@ -405,7 +876,7 @@ unsafe fn hook(base: *mut c_void) {
                 ret
             }
         }
-        SHIP_MGR_COLL.init(base, ship_mgr_coll_hook);
+        SHIP_MGR_COLL.init(base, "ShipManager::CollisionMoving", ship_mgr_coll_hook);
     }
     {
         static mut DRONE_COLL: cross::Hook5<
@@ -425,11 +896,11 @@ unsafe fn hook(base: *mut c_void) {
                 ret
             }
         }
-        DRONE_COLL.init(base, drone_coll_hook);
+        DRONE_COLL.init(base, "SpaceDrone::CollisionMoving", drone_coll_hook);
     }
 
     macro_rules! hook_proj_collchk {
-        ($(($a:expr, $b:expr),)+) => {
+        ($(($name:literal, $a:expr, $b:expr),)+) => {
             $({
                 static mut PROJECTILE_COLLCHK: cross::Hook2<
                     $a,
@@ -445,19 +916,19 @@ unsafe fn hook(base: *mut c_void) {
                         game::projectile_post_collchk(proj, obj);
                     }
                 }
-                PROJECTILE_COLLCHK.init(base, projectile_collchk_hook);
+                PROJECTILE_COLLCHK.init(base, $name, projectile_collchk_hook);
             })+
         };
     }
     hook_proj_collchk!(
         // Projectile
-        (0x459550, 0x446D80),
+        ("Projectile::CollisionCheck", 0x459550, 0x446D80),
         // BeamWeapon
-        (0x438C30, 0x42F340),
+        ("BeamWeapon::CollisionCheck", 0x438C30, 0x42F340),
         // BombProjectile
-        (0x450080, 0x4335E0),
+        ("BombProjectile::CollisionCheck", 0x450080, 0x4335E0),
         // PDSFire
-        (0x456EA0, 0x446730),
+        ("PDSFire::CollisionCheck", 0x456EA0, 0x446730),
     );
     // sun: 4A4E80/4C0C70
     // pulsar: 4A36B0/4BD750
@@ -471,7 +942,7 @@ unsafe fn hook(base: *mut c_void) {
                 game::sun_damage(mgr);
             }
         }
-        SUN_DAMAGE.init(base, sundmg_hook);
+        SUN_DAMAGE.init(base, "ShipManager sun damage", sundmg_hook);
         static mut PULSAR_DAMAGE: cross::Hook1<0x4A36B0, 0x4BD750, *mut ShipManager, ()> =
             cross::Hook1::new();
         cross_fn! {
@@ -481,8 +952,11 @@ unsafe fn hook(base: *mut c_void) {
                 game::pulsar_damage(mgr);
             }
         }
-        PULSAR_DAMAGE.init(base, pulsardmg_hook);
+        PULSAR_DAMAGE.init(base, "ShipManager pulsar damage", pulsardmg_hook);
     }
+
+    cross::log_report();
+    cross::log_hooks();
 }
 
 #[cfg_attr(target_os = "linux", ctor)]
@@ -547,3 +1021,124 @@ unsafe fn init() {
         hook(base);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{DataPaths, TextLibrary};
+
+    #[test]
+    fn test_text_language() {
+        let text = r#"
+            <FTL>
+            <text name="a">Hello</text>
+            <text name="b">Bye</text>
+            <text name="a" language="de">Hallo</text>
+            <text name="c" language="de">Nur Deutsch</text>
+            </FTL>
+        "#;
+        let mut lib = TextLibrary::default();
+        lib.add_text(quick_xml::de::from_str(text).unwrap());
+        assert_eq!(lib.text_in(None, "a"), Some("Hello"));
+        assert_eq!(lib.text_in(Some("de"), "a"), Some("Hallo"));
+        assert_eq!(lib.text_in(Some("de"), "b"), Some("Bye"));
+        assert_eq!(lib.text_in(Some("ru"), "a"), Some("Hello"));
+        assert_eq!(lib.text_in(None, "c"), None);
+        // outside of the game the language is unknown
+        assert_eq!(lib.text("a"), Some("Hello"));
+    }
+
+    #[test]
+    fn test_system_title() {
+        let text = r#"
+            <FTL>
+            <systemBlueprint name="drones">
+                <type>drones</type>
+                <title id="drones_title"/>
+                <desc id="drones_desc"/>
+                <startPower>2</startPower>
+                <maxPower>8</maxPower>
+                <rarity>0</rarity>
+                <upgradeCost><level>10</level></upgradeCost>
+                <cost>70</cost>
+            </systemBlueprint>
+            </FTL>
+        "#;
+        let mut lib = TextLibrary::default();
+        lib.add_blueprints(quick_xml::de::from_str(text).unwrap());
+        // the text id isn't shown when the text is missing
+        assert_eq!(
+            crate::bindings::System::Drones.title_in(&lib),
+            "Drone Control"
+        );
+        lib.add_text(
+            quick_xml::de::from_str(r#"<FTL><text name="drones_title">Drohnen</text></FTL>"#)
+                .unwrap(),
+        );
+        assert_eq!(crate::bindings::System::Drones.title_in(&lib), "Drohnen");
+        assert_eq!(crate::bindings::System::Shields.title_in(&lib), "Shields");
+    }
+
+    #[test]
+    fn test_events() {
+        let text = r#"
+            <FTL>
+            <textList name="TEXTS">
+                <text>First</text>
+                <text>Second</text>
+            </textList>
+            <event name="A">
+                <text load="TEXTS"/>
+                <choice>
+                    <text>Go</text>
+                    <event load="LIST"/>
+                </choice>
+            </event>
+            <event name="B"><text>B</text></event>
+            <event name="C"><text>C</text><ship load="SHIP" hostile="true"/></event>
+            <eventList name="LOOP"><event load="LOOP"/></eventList>
+            <eventList name="LIST">
+                <event load="B"/>
+                <event load="C"/>
+                <event><text>Inline</text></event>
+            </eventList>
+            <ship name="SHIP" auto_blueprint="PIRATE"/>
+            </FTL>
+        "#;
+        let mut lib = TextLibrary::default();
+        lib.add_events(quick_xml::de::from_str(text).unwrap());
+        let a = lib.event("A").unwrap();
+        assert_eq!(lib.text_str(a.text.as_ref().unwrap()), Some("First"));
+        let outcomes = lib.resolve_event(&a.choices[0].event);
+        assert_eq!(
+            outcomes
+                .iter()
+                .map(|(_, e)| lib.text_str(e.text.as_ref().unwrap()).unwrap())
+                .collect::<Vec<_>>(),
+            ["B", "C", "Inline"]
+        );
+        assert!(outcomes.iter().all(|(chance, _)| *chance == 1.0 / 3.0));
+        let ship = lib.ship(outcomes[1].1.ship.as_ref().unwrap()).unwrap();
+        assert_eq!(ship.auto_blueprint.as_deref(), Some("PIRATE"));
+        assert_eq!(lib.resolve_event_name("LIST").len(), 3);
+        assert!(lib.resolve_event_name("LOOP").is_empty());
+        assert!(lib.resolve_event_name("MISSING").is_empty());
+        assert_eq!(lib.event_list("LIST").unwrap().events.len(), 3);
+        assert!(lib.text_list("TEXTS").unwrap().text.len() == 2);
+    }
+
+    #[test]
+    fn test_missing_data() {
+        let paths = DataPaths {
+            game_dir: std::env::temp_dir().join("neuro-ftl-no-such-dir"),
+            ..Default::default()
+        };
+        let err = TextLibrary::load(&paths).err().unwrap();
+        assert!(err.to_string().contains("neuro-ftl-no-such-dir"), "{err}");
+        // the test binary's directory has no game data, so this falls back to an empty library
+        // instead of panicking
+        if std::env::var_os("NEURO_FTL_DATA_DIR").is_none() {
+            assert_eq!(crate::game::strings::text("no_such_text"), "no_such_text");
+            assert_eq!(crate::bindings::System::Drones.title(), "Drone Control");
+        }
+    }
+}
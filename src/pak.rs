@@ -1,17 +1,22 @@
 use std::{
+    borrow::Cow,
     collections::BTreeMap,
     fs::File,
-    io::{self, Read, Seek},
+    io::{self, Read, Seek, Write},
     path::Path,
 };
 
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+
+/// The entry's data is zlib-compressed, `datalen` is the compressed size and `filesize` is the
+/// decompressed size.
+pub const PKG_DEFLATED: u8 = 1;
+
 #[derive(Copy, Clone, Debug)]
 pub struct PakEntry {
-    #[allow(unused)]
     hash: u32,
     nameofs_flags: u32,
     offset: u32,
-    #[allow(unused)]
     datalen: u32,
     filesize: u32,
 }
@@ -23,10 +28,14 @@ impl PakEntry {
     pub fn flags(&self) -> u8 {
         (self.nameofs_flags >> 24) as u8
     }
+    pub fn compressed(&self) -> bool {
+        self.flags() & PKG_DEFLATED != 0
+    }
 }
 
 pub struct Pak {
     file: File,
+    len: u64,
     // entries: Box<[Entry]>,
     // names: Box<[u8]>,
     index: BTreeMap<Box<[u8]>, PakEntry>,
@@ -35,6 +44,7 @@ pub struct Pak {
 impl Pak {
     pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
         let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
         let mut header = [0u8; 16];
         file.read_exact(&mut header)?;
         let header_size = u16::from_be_bytes([header[4], header[5]]);
@@ -77,6 +87,7 @@ impl Pak {
         }
         Ok(Self {
             file,
+            len,
             // entries,
             // names,
             index,
@@ -87,17 +98,85 @@ impl Pak {
             .index
             .get(file.as_bytes())
             .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "file not found in pak index"))?;
-        if entry.flags() != 0 {
+        if entry.flags() & !PKG_DEFLATED != 0 {
             return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "pak compression not implemented",
+                io::ErrorKind::InvalidData,
+                format!("unsupported pak entry flags: {:#x}", entry.flags()),
             ));
         }
         self.file.seek(io::SeekFrom::Start(entry.offset.into()))?;
-        let mut ret = vec![0u8; usize::try_from(entry.filesize).unwrap()];
-        self.file.read_exact(&mut ret)?;
+        let filesize = usize::try_from(entry.filesize).unwrap();
+        if !entry.compressed() {
+            let mut ret = vec![0u8; filesize];
+            self.file.read_exact(&mut ret)?;
+            return Ok(ret);
+        }
+        let mut ret = Vec::with_capacity(filesize);
+        ZlibDecoder::new((&mut self.file).take(entry.datalen.into())).read_to_end(&mut ret)?;
+        if ret.len() != filesize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "decompressed size mismatch: expected {filesize}, got {}",
+                    ret.len()
+                ),
+            ));
+        }
         Ok(ret)
     }
+    /// Cheap sanity check of a file's index entry, this doesn't read the file's contents.
+    ///
+    /// Note that the stored hash is a hash of the path rather than of the contents, so a mismatch
+    /// means the index itself is damaged.
+    pub fn verify_entry(&self, file: &str) -> io::Result<()> {
+        let entry = self
+            .index
+            .get(file.as_bytes())
+            .ok_or_else(|| io::Error::other("file not found in pak index"))?;
+        let hash = path_hash(file.as_bytes());
+        if entry.hash != hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "path hash mismatch: stored {:08x}, expected {hash:08x}",
+                    entry.hash
+                ),
+            ));
+        }
+        if !entry.compressed() && entry.datalen != entry.filesize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "stored size {} doesn't match file size {}",
+                    entry.datalen, entry.filesize
+                ),
+            ));
+        }
+        if u64::from(entry.offset) + u64::from(entry.datalen) > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "data at {:#x}..{:#x} is past the end of the pak ({:#x})",
+                    entry.offset,
+                    u64::from(entry.offset) + u64::from(entry.datalen),
+                    self.len
+                ),
+            ));
+        }
+        Ok(())
+    }
+    /// Check every entry, including decompressing compressed data. Returns the list of damaged
+    /// files along with what's wrong with them.
+    pub fn verify(&mut self) -> Vec<(String, io::Error)> {
+        let mut ret = Vec::new();
+        for name in self.index.keys().cloned().collect::<Vec<_>>() {
+            let name = String::from_utf8_lossy(&name).into_owned();
+            if let Err(err) = self.verify_entry(&name).and_then(|()| self.read(&name)) {
+                ret.push((name, err));
+            }
+        }
+        ret
+    }
     pub fn file_list(&self) -> Vec<String> {
         self.index
             .keys()
@@ -106,88 +185,530 @@ impl Pak {
     }
 }
 
-/*pub struct WindowsDatEntry {
-    offset: usize,
+/// The path hash FTL stores in every pak entry (case-insensitive).
+pub fn path_hash(name: &[u8]) -> u32 {
+    name.iter().fold(0u32, |hash, c| {
+        hash.rotate_right(5) ^ u32::from(c.to_ascii_lowercase())
+    })
+}
+
+struct PakBuilderEntry {
+    data: Vec<u8>,
+    compress: bool,
+}
+
+/// Writes files into a new pak file, the output can be read back with [`Pak::open`].
+#[derive(Default)]
+pub struct PakBuilder {
+    files: BTreeMap<Box<[u8]>, PakBuilderEntry>,
+}
+
+impl PakBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a file, replacing any previous file with the same name.
+    pub fn add(&mut self, name: &str, data: impl Into<Vec<u8>>) -> &mut Self {
+        self.files.insert(
+            name.as_bytes().into(),
+            PakBuilderEntry {
+                data: data.into(),
+                compress: false,
+            },
+        );
+        self
+    }
+    /// Add a file that will be stored zlib-compressed.
+    pub fn add_compressed(&mut self, name: &str, data: impl Into<Vec<u8>>) -> &mut Self {
+        self.files.insert(
+            name.as_bytes().into(),
+            PakBuilderEntry {
+                data: data.into(),
+                compress: true,
+            },
+        );
+        self
+    }
+    pub fn remove(&mut self, name: &str) -> &mut Self {
+        self.files.remove(name.as_bytes());
+        self
+    }
+    pub fn file_list(&self) -> Vec<String> {
+        self.files
+            .keys()
+            .flat_map(|x| std::str::from_utf8(x).ok().map(|x| x.to_owned()))
+            .collect()
+    }
+    pub fn write(&self, mut out: impl Write) -> io::Result<()> {
+        fn too_big(what: &str) -> io::Error {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("{what} is too big"))
+        }
+        // the game expects entries to be sorted by hash
+        let mut files: Vec<_> = self
+            .files
+            .iter()
+            .map(|(name, entry)| (path_hash(name), name, entry))
+            .collect();
+        files.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        let mut names = Vec::new();
+        let mut datas = Vec::with_capacity(files.len());
+        let mut entries = Vec::with_capacity(files.len());
+        for (hash, name, entry) in files {
+            let nameofs = u32::try_from(names.len())
+                .ok()
+                .filter(|x| *x <= 0xFFFFFF)
+                .ok_or_else(|| too_big("pak name table"))?;
+            names.extend_from_slice(name);
+            names.push(b'\0');
+            let filesize = u32::try_from(entry.data.len()).map_err(|_| too_big("pak entry"))?;
+            let (flags, data) = if entry.compress {
+                let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+                enc.write_all(&entry.data)?;
+                (PKG_DEFLATED, Cow::Owned(enc.finish()?))
+            } else {
+                (0, Cow::Borrowed(entry.data.as_slice()))
+            };
+            let datalen = u32::try_from(data.len()).map_err(|_| too_big("pak entry"))?;
+            entries.push(PakEntry {
+                hash,
+                nameofs_flags: nameofs | (u32::from(flags) << 24),
+                offset: 0,
+                datalen,
+                filesize,
+            });
+            datas.push(data);
+        }
+        let entry_count = u32::try_from(entries.len()).map_err(|_| too_big("pak file count"))?;
+        let name_size = u32::try_from(names.len()).map_err(|_| too_big("pak name table"))?;
+        let mut offset = 16 + 20 * u64::from(entry_count) + u64::from(name_size);
+        for entry in &mut entries {
+            entry.offset = u32::try_from(offset).map_err(|_| too_big("pak file"))?;
+            offset += u64::from(entry.datalen);
+        }
+        u32::try_from(offset).map_err(|_| too_big("pak file"))?;
+
+        out.write_all(b"PKG\n")?;
+        out.write_all(&16u16.to_be_bytes())?;
+        out.write_all(&20u16.to_be_bytes())?;
+        out.write_all(&entry_count.to_be_bytes())?;
+        out.write_all(&name_size.to_be_bytes())?;
+        for entry in &entries {
+            out.write_all(&entry.hash.to_be_bytes())?;
+            out.write_all(&entry.nameofs_flags.to_be_bytes())?;
+            out.write_all(&entry.offset.to_be_bytes())?;
+            out.write_all(&entry.datalen.to_be_bytes())?;
+            out.write_all(&entry.filesize.to_be_bytes())?;
+        }
+        out.write_all(&names)?;
+        for data in datas {
+            out.write_all(&data)?;
+        }
+        out.flush()
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write(io::BufWriter::new(File::create(path)?))
+    }
+}
+
+pub struct WindowsDatEntry {
+    offset: u64,
     filesize: usize,
 }
 
+/// The legacy data format used by older game builds (`data.dat`/`resource.dat`): a little-endian
+/// list of offsets, each pointing at a size, a name length, the name and the file's contents.
 pub struct WindowsDat {
     file: File,
+    len: u64,
     index: BTreeMap<Box<[u8]>, WindowsDatEntry>,
 }
 
 impl WindowsDat {
     pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
         let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
         let mut header = [0u8; 4];
         file.read_exact(&mut header)?;
-        let index_size = usize::try_from(u32::from_le_bytes(header)).unwrap();
-        let mut indices = vec![0u8; 4 * index_size];
+        let index_size = u32::from_le_bytes(header);
+        if 4 + 4 * u64::from(index_size) > len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid dat index",
+            ));
+        }
+        let mut indices = vec![0u8; 4 * usize::try_from(index_size).unwrap()];
         file.read_exact(&mut indices)?;
-        let index = indices
-            .chunks_exact(4)
-            .map(|offset| {
-                let offset = usize::try_from(u32::from_le_bytes([
-                    offset[0], offset[1], offset[2], offset[3],
-                ]))
-                .unwrap();
-                file.seek(io::SeekFrom::Start(offset as u64))?;
-                let mut size_namelen = [0u8; 8];
-                file.read_exact(&mut size_namelen)?;
-                let size = usize::try_from(u32::from_le_bytes([
-                    size_namelen[0],
-                    size_namelen[1],
-                    size_namelen[2],
-                    size_namelen[3],
-                ]))
-                .unwrap();
-                let namelen = usize::try_from(u32::from_le_bytes([
-                    size_namelen[4],
-                    size_namelen[5],
-                    size_namelen[6],
-                    size_namelen[7],
-                ]))
-                .unwrap();
-                let mut name = vec![0u8; namelen];
-                file.read_exact(&mut name)?;
-
-                Ok((
-                    name.into_boxed_slice(),
-                    WindowsDatEntry {
-                        offset: offset + size_namelen.len() + namelen,
-                        filesize: size,
-                    },
-                ))
-            })
-            .collect::<io::Result<_>>()?;
-        Ok(Self { file, index })
+        let mut index = BTreeMap::new();
+        for offset in indices.chunks_exact(4) {
+            let offset = u64::from(u32::from_le_bytes([
+                offset[0], offset[1], offset[2], offset[3],
+            ]));
+            // unused index slots
+            if offset == 0 {
+                continue;
+            }
+            file.seek(io::SeekFrom::Start(offset))?;
+            let mut size_namelen = [0u8; 8];
+            file.read_exact(&mut size_namelen)?;
+            let size = u32::from_le_bytes([
+                size_namelen[0],
+                size_namelen[1],
+                size_namelen[2],
+                size_namelen[3],
+            ]);
+            let namelen = u32::from_le_bytes([
+                size_namelen[4],
+                size_namelen[5],
+                size_namelen[6],
+                size_namelen[7],
+            ]);
+            if offset + 8 + u64::from(namelen) > len {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid dat entry at {offset:#x}"),
+                ));
+            }
+            let mut name = vec![0u8; usize::try_from(namelen).unwrap()];
+            file.read_exact(&mut name)?;
+            index.insert(
+                name.into_boxed_slice(),
+                WindowsDatEntry {
+                    offset: offset + size_namelen.len() as u64 + u64::from(namelen),
+                    filesize: usize::try_from(size).unwrap(),
+                },
+            );
+        }
+        Ok(Self { file, len, index })
     }
     pub fn read(&mut self, file: &str) -> io::Result<Vec<u8>> {
         let entry = self
             .index
             .get(file.as_bytes())
             .ok_or_else(|| io::Error::new(io::ErrorKind::Other, "file not found in dat index"))?;
-        self.file.seek(io::SeekFrom::Start(entry.offset as u64))?;
+        self.file.seek(io::SeekFrom::Start(entry.offset))?;
         let mut ret = vec![0u8; entry.filesize];
         self.file.read_exact(&mut ret)?;
         Ok(ret)
     }
+    /// The legacy format has no hashes, so this only checks that the data is within the file.
+    pub fn verify_entry(&self, file: &str) -> io::Result<()> {
+        let entry = self
+            .index
+            .get(file.as_bytes())
+            .ok_or_else(|| io::Error::other("file not found in dat index"))?;
+        if entry.offset + entry.filesize as u64 > self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "data at {:#x}..{:#x} is past the end of the dat ({:#x})",
+                    entry.offset,
+                    entry.offset + entry.filesize as u64,
+                    self.len
+                ),
+            ));
+        }
+        Ok(())
+    }
+    pub fn verify(&mut self) -> Vec<(String, io::Error)> {
+        let mut ret = Vec::new();
+        for name in self.index.keys().cloned().collect::<Vec<_>>() {
+            let name = String::from_utf8_lossy(&name).into_owned();
+            if let Err(err) = self.verify_entry(&name) {
+                ret.push((name, err));
+            }
+        }
+        ret
+    }
     pub fn file_list(&self) -> Vec<String> {
         self.index
             .keys()
             .flat_map(|x| std::str::from_utf8(x).ok().map(|x| x.to_owned()))
             .collect()
     }
-}*/
+}
 
-// #[cfg(target_os = "linux")]
-pub type Data = Pak;
-// #[cfg(target_os = "windows")]
-// pub type Data = WindowsDat;
+enum Archive {
+    Pak(Pak),
+    Dat(WindowsDat),
+}
+
+impl Archive {
+    fn open(path: &Path) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        File::open(path)?.read_exact(&mut magic)?;
+        if &magic == b"PKG\n" {
+            Pak::open(path).map(Self::Pak)
+        } else {
+            WindowsDat::open(path).map(Self::Dat)
+        }
+    }
+    fn contains(&self, file: &str) -> bool {
+        match self {
+            Self::Pak(x) => x.index.contains_key(file.as_bytes()),
+            Self::Dat(x) => x.index.contains_key(file.as_bytes()),
+        }
+    }
+}
+
+/// Game data, either the `ftl.dat` pak used by current builds or the legacy
+/// `data.dat`/`resource.dat` pair. The format is detected from the file header.
+pub struct Data {
+    archives: Vec<Archive>,
+}
+
+impl Data {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            archives: vec![Archive::open(path.as_ref())?],
+        })
+    }
+    /// Open the data files in the game directory, preferring `ftl.dat` if it exists.
+    pub fn open_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        let pak = dir.join("ftl.dat");
+        if pak.exists() {
+            return Self::open(pak);
+        }
+        let mut archives = Vec::new();
+        for name in ["data.dat", "resource.dat"] {
+            archives.push(Archive::open(&dir.join(name))?);
+        }
+        Ok(Self { archives })
+    }
+    pub fn contains(&self, file: &str) -> bool {
+        self.archives.iter().any(|x| x.contains(file))
+    }
+    fn archive(&mut self, file: &str) -> io::Result<&mut Archive> {
+        self.archives
+            .iter_mut()
+            .find(|x| x.contains(file))
+            .ok_or_else(|| io::Error::other("file not found in data index"))
+    }
+    pub fn read(&mut self, file: &str) -> io::Result<Vec<u8>> {
+        match self.archive(file)? {
+            Archive::Pak(x) => x.read(file),
+            Archive::Dat(x) => x.read(file),
+        }
+    }
+    pub fn verify_entry(&mut self, file: &str) -> io::Result<()> {
+        match self.archive(file)? {
+            Archive::Pak(x) => x.verify_entry(file),
+            Archive::Dat(x) => x.verify_entry(file),
+        }
+    }
+    pub fn verify(&mut self) -> Vec<(String, io::Error)> {
+        self.archives
+            .iter_mut()
+            .flat_map(|x| match x {
+                Archive::Pak(x) => x.verify(),
+                Archive::Dat(x) => x.verify(),
+            })
+            .collect()
+    }
+    pub fn file_list(&self) -> Vec<String> {
+        let mut ret: Vec<_> = self
+            .archives
+            .iter()
+            .flat_map(|x| match x {
+                Archive::Pak(x) => x.file_list(),
+                Archive::Dat(x) => x.file_list(),
+            })
+            .collect();
+        ret.sort_unstable();
+        ret.dedup();
+        ret
+    }
+}
 
 #[cfg(test)]
 mod test {
-    use super::Pak;
+    use std::io::Write;
+
+    use flate2::{write::ZlibEncoder, Compression};
+
+    use super::{Data, Pak, PakBuilder, PKG_DEFLATED};
+
+    // (name, flags, stored data, file size)
+    fn write_pak(name: &str, files: &[(&str, u8, Vec<u8>, usize)]) -> std::path::PathBuf {
+        let mut names = Vec::new();
+        let mut name_offsets = Vec::new();
+        for (name, ..) in files {
+            name_offsets.push(names.len() as u32);
+            names.extend_from_slice(name.as_bytes());
+            names.push(0);
+        }
+        let mut offset = 16 + 20 * files.len() + names.len();
+        let mut out = Vec::new();
+        out.extend_from_slice(b"PKG\n");
+        out.extend_from_slice(&16u16.to_be_bytes());
+        out.extend_from_slice(&20u16.to_be_bytes());
+        out.extend_from_slice(&(files.len() as u32).to_be_bytes());
+        out.extend_from_slice(&(names.len() as u32).to_be_bytes());
+        for ((name, flags, data, size), nameofs) in files.iter().zip(name_offsets) {
+            out.extend_from_slice(&super::path_hash(name.as_bytes()).to_be_bytes());
+            out.extend_from_slice(&(nameofs | (u32::from(*flags) << 24)).to_be_bytes());
+            out.extend_from_slice(&(offset as u32).to_be_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            out.extend_from_slice(&(*size as u32).to_be_bytes());
+            offset += data.len();
+        }
+        out.extend_from_slice(&names);
+        for (_, _, data, _) in files {
+            out.extend_from_slice(data);
+        }
+        let path =
+            std::env::temp_dir().join(format!("neuro-ftl-{}-{name}.dat", std::process::id()));
+        std::fs::write(&path, out).unwrap();
+        path
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
+        enc.write_all(data).unwrap();
+        enc.finish().unwrap()
+    }
+
+    #[test]
+    fn test_compressed() {
+        let text = b"<?xml version=\"1.0\"?><FTL><text name=\"a\">aaaaaaaaaaaaaaaa</text></FTL>";
+        let path = write_pak(
+            "compressed",
+            &[
+                ("data/raw.xml", 0, text.to_vec(), text.len()),
+                ("data/packed.xml", PKG_DEFLATED, deflate(text), text.len()),
+                ("data/empty.xml", PKG_DEFLATED, deflate(b""), 0),
+            ],
+        );
+        let mut file = Pak::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(file.read("data/raw.xml").unwrap(), text);
+        assert_eq!(file.read("data/packed.xml").unwrap(), text);
+        assert_eq!(file.read("data/empty.xml").unwrap(), b"");
+    }
+
+    #[test]
+    fn test_builder() {
+        let path =
+            std::env::temp_dir().join(format!("neuro-ftl-{}-builder.dat", std::process::id()));
+        let big = "FTL".repeat(1000);
+        let mut builder = PakBuilder::new();
+        builder
+            .add("data/blueprints.xml", "<FTL></FTL>")
+            .add_compressed("data/text_misc.xml", big.clone())
+            .add("img/empty.png", [])
+            .add("data/removed.xml", "x")
+            .remove("data/removed.xml");
+        builder.save(&path).unwrap();
+        let mut file = Pak::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            file.file_list(),
+            ["data/blueprints.xml", "data/text_misc.xml", "img/empty.png"]
+        );
+        assert_eq!(file.read("data/blueprints.xml").unwrap(), b"<FTL></FTL>");
+        assert_eq!(file.read("data/text_misc.xml").unwrap(), big.as_bytes());
+        assert_eq!(file.read("img/empty.png").unwrap(), b"");
+        assert!(file.read("data/removed.xml").is_err());
+        let entry = file.index[b"data/text_misc.xml".as_slice()];
+        assert!(entry.compressed());
+        assert!(entry.datalen < entry.filesize);
+        assert_eq!(entry.hash, super::path_hash(b"DATA/Text_Misc.xml"));
+    }
+
+    #[test]
+    fn test_verify() {
+        let text = b"<FTL></FTL>";
+        let path = write_pak(
+            "verify",
+            &[
+                ("ok", PKG_DEFLATED, deflate(text), text.len()),
+                ("hash", 0, text.to_vec(), text.len()),
+                ("deflate", PKG_DEFLATED, text.to_vec(), text.len()),
+            ],
+        );
+        let mut file = Pak::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        file.index.get_mut(b"hash".as_slice()).unwrap().hash ^= 1;
+        file.index.get_mut(b"ok".as_slice()).unwrap().datalen += 1000;
+        let bad: Vec<_> = file.verify().into_iter().map(|x| x.0).collect();
+        assert_eq!(bad, ["deflate", "hash", "ok"]);
+        file.index.get_mut(b"ok".as_slice()).unwrap().datalen -= 1000;
+        let bad: Vec<_> = file.verify().into_iter().map(|x| x.0).collect();
+        assert_eq!(bad, ["deflate", "hash"]);
+        assert!(file.verify_entry("deflate").is_ok());
+        assert!(file.verify_entry("hash").is_err());
+    }
+
+    fn write_dat(path: &std::path::Path, files: &[(&str, &[u8])]) {
+        // leave an unused slot at the end of the index
+        let mut offset = 4 + 4 * (files.len() + 1);
+        let mut index = Vec::new();
+        let mut data = Vec::new();
+        index.extend_from_slice(&(files.len() as u32 + 1).to_le_bytes());
+        for (name, contents) in files {
+            index.extend_from_slice(&(offset as u32).to_le_bytes());
+            data.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(contents);
+            offset += 8 + name.len() + contents.len();
+        }
+        index.extend_from_slice(&0u32.to_le_bytes());
+        index.extend_from_slice(&data);
+        std::fs::write(path, index).unwrap();
+    }
+
+    #[test]
+    fn test_data() {
+        let dir = std::env::temp_dir().join(format!("neuro-ftl-{}-data", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_dat(
+            &dir.join("data.dat"),
+            &[("data/text_misc.xml", b"<FTL/>"), ("data/a.xml", b"")],
+        );
+        write_dat(&dir.join("resource.dat"), &[("img/a.png", b"png")]);
+
+        let mut dat = Data::open(dir.join("data.dat")).unwrap();
+        assert_eq!(dat.file_list(), ["data/a.xml", "data/text_misc.xml"]);
+        assert_eq!(dat.read("data/text_misc.xml").unwrap(), b"<FTL/>");
+        assert!(dat.verify().is_empty());
+
+        let mut data = Data::open_dir(&dir).unwrap();
+        assert_eq!(
+            data.file_list(),
+            ["data/a.xml", "data/text_misc.xml", "img/a.png"]
+        );
+        assert_eq!(data.read("img/a.png").unwrap(), b"png");
+        assert_eq!(data.read("data/a.xml").unwrap(), b"");
+        assert!(data.read("img/b.png").is_err());
+
+        let mut builder = PakBuilder::new();
+        builder.add("data/text_misc.xml", "<FTL></FTL>");
+        builder.save(dir.join("ftl.dat")).unwrap();
+        let mut data = Data::open_dir(&dir).unwrap();
+        assert_eq!(data.file_list(), ["data/text_misc.xml"]);
+        assert_eq!(data.read("data/text_misc.xml").unwrap(), b"<FTL></FTL>");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compressed_invalid() {
+        let text = b"hello world";
+        let path = write_pak(
+            "compressed-invalid",
+            &[
+                ("garbage", PKG_DEFLATED, text.to_vec(), text.len()),
+                ("size", PKG_DEFLATED, deflate(text), text.len() + 1),
+                ("flags", 0x80, text.to_vec(), text.len()),
+            ],
+        );
+        let mut file = Pak::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(file.read("garbage").is_err());
+        assert!(file.read("size").is_err());
+        assert!(file.read("flags").is_err());
+    }
 
     #[test]
     fn test() {
@@ -204,27 +725,29 @@ mod test {
                 continue;
             }
             let v = file.read(s).unwrap();
-            let v = std::str::from_utf8(&v)
-                .unwrap()
-                .replace("</event>-", "</event>")
-                .replace("/>.\r", "/>\r")
-                .replace(">1.f<", ">1.0<");
+            let mut diagnostics = Vec::new();
+            let doc = crate::xml::lenient::preprocess(s, &v, &mut diagnostics);
+            let v = doc.text();
             if v.contains("<event ") && !v.contains("<sectorDescription ") {
-                if let Err(err) = quick_xml::de::from_str::<crate::xml::XmlEvents>(&v) {
+                if let Err(err) = doc.parse_all::<crate::xml::XmlEvents>() {
                     eprintln!("{s}");
                     panic!("{err}");
                 }
             } else if s.contains("luep") && !s.contains("text_") {
-                if let Err(err) = quick_xml::de::from_str::<crate::xml::XmlBlueprints>(&v) {
+                if let Err(err) = doc.parse_all::<crate::xml::XmlBlueprints>() {
                     eprintln!("{s}");
                     panic!("{err}");
                 }
             } else if s.contains("text_") {
-                if let Err(err) = quick_xml::de::from_str::<crate::xml::XmlText>(&v) {
+                if let Err(err) = doc.parse_all::<crate::xml::XmlText>() {
                     eprintln!("{s}");
                     panic!("{err}");
                 }
             }
+            // vanilla data only needs small repairs
+            for d in diagnostics {
+                assert_eq!(d.level, log::Level::Info, "{d}");
+            }
         }
     }
 }
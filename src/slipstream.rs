@@ -0,0 +1,610 @@
+//! Slipstream `.ftl` mods: zip files whose contents either replace game files, or are appended to
+//! them (`*.xml.append`, `*.xml.rawappend`). Appended XML may contain `mod:` tags that find and
+//! patch existing elements.
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use quick_xml::events::{BytesStart, Event};
+use zip::ZipArchive;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ModOp {
+    /// Replace the file
+    Replace,
+    /// Append the XML, applying `mod:` patches
+    Append,
+    /// Append the file's contents as is
+    RawAppend,
+}
+
+impl ModOp {
+    /// Split a path inside a mod into the game file it targets and the operation.
+    pub fn parse(name: &str) -> (String, Self) {
+        for (suffix, target_suffix, op) in [
+            (".xml.append", ".xml", Self::Append),
+            (".append.xml", ".xml", Self::Append),
+            (".xml.rawappend", ".xml", Self::RawAppend),
+            (".rawappend.xml", ".xml", Self::RawAppend),
+            (".xml.rawclobber", ".xml", Self::Replace),
+            (".rawclobber.xml", ".xml", Self::Replace),
+        ] {
+            if let Some(base) = name.strip_suffix(suffix) {
+                return (format!("{base}{target_suffix}"), op);
+            }
+        }
+        (name.to_owned(), Self::Replace)
+    }
+}
+
+pub struct Mod {
+    name: String,
+    archive: ZipArchive<BufReader<File>>,
+    /// target file -> operations and their zip entry indices, in archive order
+    files: BTreeMap<String, Vec<(ModOp, usize)>>,
+}
+
+impl Mod {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+        let mut files = BTreeMap::<String, Vec<_>>::new();
+        for i in 0..archive.len() {
+            let file = archive.by_index(i)?;
+            if file.is_dir() {
+                continue;
+            }
+            let name = file.name().replace('\\', "/");
+            if name.starts_with("mod-appendix/") {
+                continue;
+            }
+            let (target, op) = ModOp::parse(&name);
+            files.entry(target).or_default().push((op, i));
+        }
+        Ok(Self {
+            name: path
+                .file_name()
+                .map(|x| x.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            archive,
+            files,
+        })
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Game files this mod replaces or appends to.
+    pub fn file_list(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(|x| x.as_str())
+    }
+    /// Whether this mod replaces the file (as opposed to only appending to it).
+    pub fn replaces(&self, file: &str) -> bool {
+        self.files
+            .get(file)
+            .is_some_and(|ops| ops.iter().any(|(op, _)| *op == ModOp::Replace))
+    }
+    /// Apply this mod's changes to a file. Patches that fail to apply are skipped with a warning.
+    pub fn apply(&mut self, file: &str, mut base: Option<Vec<u8>>) -> io::Result<Option<Vec<u8>>> {
+        let Some(ops) = self.files.get(file) else {
+            return Ok(base);
+        };
+        for &(op, index) in ops {
+            let mut data = Vec::new();
+            self.archive.by_index(index)?.read_to_end(&mut data)?;
+            base = Some(match (op, base) {
+                (ModOp::Replace, _) | (_, None) => data,
+                (ModOp::RawAppend, Some(mut base)) => {
+                    base.push(b'\n');
+                    base.extend_from_slice(&data);
+                    base
+                }
+                (ModOp::Append, Some(base)) => {
+                    match patch(
+                        &String::from_utf8_lossy(&base),
+                        &String::from_utf8_lossy(&data),
+                    ) {
+                        Ok(x) => x.into_bytes(),
+                        Err(err) => {
+                            log::warn!("{}: failed to patch {file}, skipping: {err}", self.name);
+                            base
+                        }
+                    }
+                }
+            });
+        }
+        Ok(base)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Element(Element),
+    /// Raw (escaped) text
+    Text(String),
+    CData(String),
+    Comment(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Element {
+    name: String,
+    /// Unescaped attribute values
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Element {
+    fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            attrs: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+    fn from_start(e: &BytesStart) -> io::Result<Self> {
+        let mut ret = Self::new(String::from_utf8_lossy(e.name().as_ref()));
+        for attr in e.attributes().with_checks(false) {
+            let attr = attr.map_err(|err| invalid(err.to_string()))?;
+            let value = match attr.unescape_value() {
+                Ok(x) => x.into_owned(),
+                Err(_) => String::from_utf8_lossy(&attr.value).into_owned(),
+            };
+            ret.attrs.push((
+                String::from_utf8_lossy(attr.key.as_ref()).into_owned(),
+                value,
+            ));
+        }
+        Ok(ret)
+    }
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+    fn set_attr(&mut self, name: &str, value: &str) {
+        if let Some((_, v)) = self.attrs.iter_mut().find(|(k, _)| k == name) {
+            *v = value.to_owned();
+        } else {
+            self.attrs.push((name.to_owned(), value.to_owned()));
+        }
+    }
+    fn text(&self) -> String {
+        self.children
+            .iter()
+            .filter_map(|x| match x {
+                Node::Text(x) | Node::CData(x) => Some(x.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+    fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|x| match x {
+            Node::Element(x) => Some(x),
+            _ => None,
+        })
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Parse an XML fragment, which may have more than one root and unclosed tags.
+fn parse(data: &str) -> io::Result<Vec<Node>> {
+    let mut reader = quick_xml::Reader::from_str(data);
+    let config = reader.config_mut();
+    config.check_end_names = false;
+    config.allow_unmatched_ends = true;
+    let mut stack = vec![Element::new("")];
+    loop {
+        let node = match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                stack.push(Element::from_start(&e)?);
+                continue;
+            }
+            Ok(Event::Empty(e)) => Node::Element(Element::from_start(&e)?),
+            Ok(Event::End(_)) => {
+                if stack.len() == 1 {
+                    continue;
+                }
+                Node::Element(stack.pop().unwrap())
+            }
+            Ok(Event::Text(e)) => Node::Text(String::from_utf8_lossy(&e).into_owned()),
+            Ok(Event::CData(e)) => Node::CData(String::from_utf8_lossy(&e).into_owned()),
+            Ok(Event::Comment(e)) => Node::Comment(String::from_utf8_lossy(&e).into_owned()),
+            Ok(Event::Decl(_) | Event::PI(_) | Event::DocType(_)) => continue,
+            Ok(Event::Eof) => break,
+            Err(err) => {
+                return Err(invalid(format!(
+                    "{err} at byte {}",
+                    reader.error_position()
+                )))
+            }
+        };
+        stack.last_mut().unwrap().children.push(node);
+    }
+    // close unclosed tags
+    while stack.len() > 1 {
+        let elem = stack.pop().unwrap();
+        stack.last_mut().unwrap().children.push(Node::Element(elem));
+    }
+    Ok(stack.pop().unwrap().children)
+}
+
+fn write(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Element(e) => {
+                out.push('<');
+                out.push_str(&e.name);
+                for (k, v) in &e.attrs {
+                    out.push(' ');
+                    out.push_str(k);
+                    out.push_str("=\"");
+                    out.push_str(&quick_xml::escape::escape(v));
+                    out.push('"');
+                }
+                if e.children.is_empty() {
+                    out.push_str("/>");
+                } else {
+                    out.push('>');
+                    write(&e.children, out);
+                    out.push_str("</");
+                    out.push_str(&e.name);
+                    out.push('>');
+                }
+            }
+            Node::Text(x) => out.push_str(x),
+            Node::CData(x) => {
+                out.push_str("<![CDATA[");
+                out.push_str(x);
+                out.push_str("]]>");
+            }
+            Node::Comment(x) => {
+                out.push_str("<!--");
+                out.push_str(x);
+                out.push_str("-->");
+            }
+        }
+    }
+}
+
+/// Get the root of a document. Returns the `<FTL>` element if that's the only root, or wraps
+/// everything in an `<FTL>` element otherwise.
+fn into_root(nodes: Vec<Node>) -> (Element, bool) {
+    let mut roots = nodes.iter().filter(|x| matches!(x, Node::Element(_)));
+    if let (Some(Node::Element(e)), None) = (roots.next(), roots.next()) {
+        if e.name == "FTL" {
+            let Some(Node::Element(e)) = nodes.into_iter().find(|x| matches!(x, Node::Element(_)))
+            else {
+                unreachable!()
+            };
+            return (e, true);
+        }
+    }
+    let mut root = Element::new("FTL");
+    root.children = nodes;
+    (root, false)
+}
+
+fn is_find(name: &str) -> bool {
+    matches!(
+        name,
+        "mod:findName" | "mod:findLike" | "mod:findWithChildLike" | "mod:findComposite"
+    )
+}
+
+fn bool_attr(e: &Element, name: &str, default: bool) -> io::Result<bool> {
+    match e.attr(name) {
+        None => Ok(default),
+        Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        Some(x) => Err(invalid(format!("invalid {name} value in {}: {x}", e.name))),
+    }
+}
+
+fn selector_matches(e: &Element, selector: Option<&Element>) -> bool {
+    let Some(selector) = selector else {
+        return true;
+    };
+    let value = selector.text();
+    selector.attrs.iter().all(|(k, v)| e.attr(k) == Some(v))
+        && (value.trim().is_empty() || e.text().trim() == value.trim())
+}
+
+/// Find the indices of children of `ctx` that match a find tag.
+fn find(ctx: &Element, cmd: &Element) -> io::Result<Vec<usize>> {
+    let find_name = cmd.name == "mod:findName";
+    let reverse = bool_attr(cmd, "reverse", find_name)?;
+    let panic = bool_attr(cmd, "panic", false)?;
+    let start: usize = cmd
+        .attr("start")
+        .map_or(Ok(0), str::parse)
+        .map_err(|err| invalid(format!("invalid start value in {}: {err}", cmd.name)))?;
+    let limit: i64 = cmd
+        .attr("limit")
+        .map_or(Ok(if find_name { 1 } else { -1 }), str::parse)
+        .map_err(|err| invalid(format!("invalid limit value in {}: {err}", cmd.name)))?;
+    let ty = cmd.attr("type");
+    let selector = cmd.elements().find(|x| x.name == "mod:selector");
+    let children = ctx
+        .children
+        .iter()
+        .enumerate()
+        .filter_map(|(i, x)| match x {
+            Node::Element(x) if ty.is_none_or(|ty| x.name == ty) => Some((i, x)),
+            _ => None,
+        });
+    let mut matches: Vec<usize> = match cmd.name.as_str() {
+        "mod:findName" => {
+            let name = cmd
+                .attr("name")
+                .ok_or_else(|| invalid("mod:findName without a name"))?;
+            children
+                .filter(|(_, x)| x.attr("name") == Some(name))
+                .map(|(i, _)| i)
+                .collect()
+        }
+        "mod:findLike" => children
+            .filter(|(_, x)| selector_matches(x, selector))
+            .map(|(i, _)| i)
+            .collect(),
+        "mod:findWithChildLike" => {
+            let child_ty = cmd
+                .attr("child-type")
+                .ok_or_else(|| invalid("mod:findWithChildLike without a child-type"))?;
+            children
+                .filter(|(_, x)| {
+                    x.elements()
+                        .any(|x| x.name == child_ty && selector_matches(x, selector))
+                })
+                .map(|(i, _)| i)
+                .collect()
+        }
+        "mod:findComposite" => {
+            let mut pars = cmd.elements().filter(|x| x.name == "mod:par");
+            let (Some(par), None) = (pars.next(), pars.next()) else {
+                return Err(invalid("mod:findComposite must have exactly one mod:par"));
+            };
+            find_par(ctx, par)?
+        }
+        x => return Err(invalid(format!("unknown find tag {x}"))),
+    };
+    if reverse {
+        matches.reverse();
+    }
+    let matches: Vec<usize> = matches
+        .into_iter()
+        .skip(start)
+        .take(usize::try_from(limit).unwrap_or(usize::MAX))
+        .collect();
+    if panic && matches.is_empty() {
+        return Err(invalid(format!("{} found no matches", cmd.name)));
+    }
+    Ok(matches)
+}
+
+fn find_par(ctx: &Element, par: &Element) -> io::Result<Vec<usize>> {
+    let and = match par.attr("op") {
+        Some("AND") => true,
+        Some("OR") => false,
+        x => return Err(invalid(format!("invalid mod:par op: {x:?}"))),
+    };
+    let mut ret: Option<Vec<usize>> = None;
+    for e in par.elements() {
+        let mut matches = if e.name == "mod:par" {
+            find_par(ctx, e)?
+        } else if is_find(&e.name) {
+            find(ctx, e)?
+        } else {
+            return Err(invalid(format!("unexpected {} in mod:par", e.name)));
+        };
+        matches.sort_unstable();
+        ret = Some(match ret {
+            None => matches,
+            Some(prev) if and => prev.into_iter().filter(|x| matches.contains(x)).collect(),
+            Some(mut prev) => {
+                prev.extend(matches);
+                prev.sort_unstable();
+                prev.dedup();
+                prev
+            }
+        });
+    }
+    Ok(ret.unwrap_or_default())
+}
+
+/// Run a find tag against the children of `ctx` and apply its commands to the matches.
+fn apply_find(ctx: &mut Element, cmd: &Element) -> io::Result<()> {
+    let mut remove = Vec::new();
+    for i in find(ctx, cmd)? {
+        let Node::Element(target) = &mut ctx.children[i] else {
+            unreachable!()
+        };
+        for c in cmd.elements() {
+            match c.name.as_str() {
+                "mod:selector" | "mod:par" => {}
+                x if is_find(x) => apply_find(target, c)?,
+                "mod:setAttributes" => {
+                    for (k, v) in &c.attrs {
+                        target.set_attr(k, v);
+                    }
+                }
+                "mod:removeAttributes" => {
+                    target.attrs.retain(|(k, _)| c.attr(k).is_none());
+                }
+                "mod:setValue" => target.children.clone_from(&c.children),
+                "mod:removeTag" => remove.push(i),
+                x => {
+                    if let Some(name) = x.strip_prefix("mod-append:") {
+                        let mut e = c.clone();
+                        e.name = name.to_owned();
+                        target.children.push(Node::Element(e));
+                    } else if let Some(name) = x.strip_prefix("mod-overwrite:") {
+                        let mut e = c.clone();
+                        e.name = name.to_owned();
+                        let old = target
+                            .children
+                            .iter_mut()
+                            .find(|x| matches!(x, Node::Element(x) if x.name == name));
+                        if let Some(old) = old {
+                            *old = Node::Element(e);
+                        } else {
+                            target.children.push(Node::Element(e));
+                        }
+                    } else {
+                        return Err(invalid(format!("unknown command {x} in {}", cmd.name)));
+                    }
+                }
+            }
+        }
+    }
+    remove.sort_unstable();
+    remove.dedup();
+    for i in remove.into_iter().rev() {
+        ctx.children.remove(i);
+    }
+    Ok(())
+}
+
+/// Apply a `*.xml.append` file to an XML document. Regular tags are appended to the document,
+/// `mod:find*` tags patch the existing elements.
+pub fn patch(base: &str, append: &str) -> io::Result<String> {
+    let (mut root, has_root) = into_root(parse(base)?);
+    let (append, _) = into_root(parse(append)?);
+    for node in append.children {
+        match node {
+            Node::Element(e) if is_find(&e.name) => apply_find(&mut root, &e)?,
+            Node::Element(e) if e.name.starts_with("mod:") || e.name.starts_with("mod-") => {
+                return Err(invalid(format!("unexpected top-level {}", e.name)));
+            }
+            node => root.children.push(node),
+        }
+    }
+    let mut ret = String::new();
+    if has_root {
+        write(&[Node::Element(root)], &mut ret);
+    } else {
+        write(&root.children, &mut ret);
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::{patch, Mod, ModOp};
+
+    #[test]
+    fn test_op() {
+        assert_eq!(
+            ModOp::parse("data/blueprints.xml.append"),
+            ("data/blueprints.xml".into(), ModOp::Append)
+        );
+        assert_eq!(
+            ModOp::parse("data/blueprints.append.xml"),
+            ("data/blueprints.xml".into(), ModOp::Append)
+        );
+        assert_eq!(
+            ModOp::parse("data/text_misc.xml.rawappend"),
+            ("data/text_misc.xml".into(), ModOp::RawAppend)
+        );
+        assert_eq!(
+            ModOp::parse("img/a.png"),
+            ("img/a.png".into(), ModOp::Replace)
+        );
+    }
+
+    #[test]
+    fn test_patch() {
+        let base = concat!(
+            r#"<?xml version="1.0" encoding="utf-8"?>"#,
+            r#"<FTL>"#,
+            r#"<weaponBlueprint name="LASER_BURST_1"><title id="a"/><damage>1</damage></weaponBlueprint>"#,
+            r#"<weaponBlueprint name="LASER_BURST_2"><title>Burst Laser II</title><damage>1</damage></weaponBlueprint>"#,
+            r#"<crewBlueprint name="human"><title>Human</title><powerList><power>A</power></powerList></crewBlueprint>"#,
+            r#"</FTL>"#,
+        );
+        let append = concat!(
+            r#"<weaponBlueprint name="MOD_LASER"><title>Mod Laser</title></weaponBlueprint>"#,
+            r#"<mod:findName type="weaponBlueprint" name="LASER_BURST_1">"#,
+            r#"<mod:setAttributes tip="A"/>"#,
+            r#"<mod-overwrite:title>Burst Laser &amp; Co</mod-overwrite:title>"#,
+            r#"<mod-append:cost>30</mod-append:cost>"#,
+            r#"</mod:findName>"#,
+            r#"<mod:findLike type="weaponBlueprint">"#,
+            r#"<mod:findLike type="damage"><mod:setValue>2</mod:setValue></mod:findLike>"#,
+            r#"</mod:findLike>"#,
+            r#"<mod:findWithChildLike type="crewBlueprint" child-type="title">"#,
+            r#"<mod:selector>Human</mod:selector>"#,
+            r#"<mod:findLike type="powerList"><mod:removeTag/></mod:findLike>"#,
+            r#"</mod:findWithChildLike>"#,
+            r#"<mod:findComposite>"#,
+            r#"<mod:par op="AND">"#,
+            r#"<mod:findLike type="weaponBlueprint"/>"#,
+            r#"<mod:findName name="LASER_BURST_2"/>"#,
+            r#"</mod:par>"#,
+            r#"<mod:removeTag/>"#,
+            r#"</mod:findComposite>"#,
+        );
+        assert_eq!(
+            patch(base, append).unwrap(),
+            concat!(
+                r#"<FTL>"#,
+                r#"<weaponBlueprint name="LASER_BURST_1" tip="A"><title>Burst Laser &amp; Co</title><damage>2</damage><cost>30</cost></weaponBlueprint>"#,
+                r#"<crewBlueprint name="human"><title>Human</title></crewBlueprint>"#,
+                r#"<weaponBlueprint name="MOD_LASER"><title>Mod Laser</title></weaponBlueprint>"#,
+                r#"</FTL>"#,
+            )
+        );
+        assert_eq!(
+            patch(r#"<text name="a"/>"#, r#"<text name="b"/>"#).unwrap(),
+            r#"<text name="a"/><text name="b"/>"#
+        );
+        assert!(patch(base, r#"<mod:findName name="X" panic="true"/>"#).is_err());
+        assert!(patch(base, r#"<mod:setValue/>"#).is_err());
+    }
+
+    #[test]
+    fn test_mod() {
+        let path = std::env::temp_dir().join(format!("neuro-ftl-{}-mod.ftl", std::process::id()));
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("data/text_misc.xml.append", options)
+            .unwrap();
+        zip.write_all(br#"<text name="b">B</text>"#).unwrap();
+        zip.start_file("data/text_new.xml", options).unwrap();
+        zip.write_all(br#"<FTL><text name="c">C</text></FTL>"#)
+            .unwrap();
+        zip.start_file("mod-appendix/metadata.xml", options)
+            .unwrap();
+        zip.finish().unwrap();
+
+        let mut m = Mod::open(&path).unwrap();
+        assert_eq!(
+            m.file_list().collect::<Vec<_>>(),
+            ["data/text_misc.xml", "data/text_new.xml"]
+        );
+        assert!(!m.replaces("data/text_misc.xml"));
+        assert!(m.replaces("data/text_new.xml"));
+        let ret = m
+            .apply(
+                "data/text_misc.xml",
+                Some(br#"<FTL><text name="a">A</text></FTL>"#.to_vec()),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            ret,
+            br#"<FTL><text name="a">A</text><text name="b">B</text></FTL>"#
+        );
+        assert_eq!(m.apply("img/a.png", None).unwrap(), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
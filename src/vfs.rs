@@ -0,0 +1,257 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    ops::Bound,
+    path::{Path, PathBuf},
+};
+
+use crate::{pak, slipstream};
+
+enum Layer {
+    /// Loose files, `root/a/b.xml` is visible as `{prefix}a/b.xml`
+    Dir {
+        root: PathBuf,
+        prefix: String,
+    },
+    Data(pak::Data),
+    Mod(slipstream::Mod),
+}
+
+impl Layer {
+    /// Whether this layer has the whole file, so lower layers can be ignored
+    fn replaces(&self, file: &str) -> bool {
+        match self {
+            Self::Dir { root, prefix } => file
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|x| root.join(x).is_file()),
+            Self::Data(data) => data.contains(file),
+            Self::Mod(m) => m.replaces(file),
+        }
+    }
+}
+
+/// Layered view of the game data. Layers pushed later override files in earlier layers.
+#[derive(Default)]
+pub struct Vfs {
+    layers: Vec<Layer>,
+    /// file name -> index of the topmost layer that has it
+    index: BTreeMap<String, usize>,
+}
+
+impl Vfs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Open the game's data files (`ftl.dat` or `data.dat`/`resource.dat`), then the extra paks
+    /// in the order they're given, then the loose `data/` directory next to the game if it exists.
+    pub fn open_game_dir(
+        dir: impl AsRef<Path>,
+        extra_paks: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        let mut ret = Self::new();
+        ret.push_data(pak::Data::open_dir(dir)?);
+        for path in extra_paks {
+            let path = path.as_ref();
+            let data = pak::Data::open(path).map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("failed to open {}: {err}", path.display()),
+                )
+            })?;
+            log::info!("loading extra game data from {}", path.display());
+            ret.push_data(data);
+        }
+        let loose = dir.join("data");
+        if loose.is_dir() {
+            log::info!("loading loose game data from {}", loose.display());
+            ret.push_dir(loose, "data/")?;
+        }
+        Ok(ret)
+    }
+    pub fn push_data(&mut self, data: pak::Data) -> &mut Self {
+        let layer = self.layers.len();
+        for name in data.file_list() {
+            self.index.insert(name, layer);
+        }
+        self.layers.push(Layer::Data(data));
+        self
+    }
+    pub fn push_dir(&mut self, root: impl Into<PathBuf>, prefix: &str) -> io::Result<&mut Self> {
+        let root = root.into();
+        let layer = self.layers.len();
+        let mut stack = vec![(root.clone(), prefix.to_owned())];
+        while let Some((dir, prefix)) = stack.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let Ok(name) = entry.file_name().into_string() else {
+                    log::warn!("skipping non-UTF-8 path {}", entry.path().display());
+                    continue;
+                };
+                if entry.file_type()?.is_dir() {
+                    stack.push((entry.path(), format!("{prefix}{name}/")));
+                } else {
+                    self.index.insert(format!("{prefix}{name}"), layer);
+                }
+            }
+        }
+        self.layers.push(Layer::Dir {
+            root,
+            prefix: prefix.to_owned(),
+        });
+        Ok(self)
+    }
+    /// Apply a Slipstream mod on top of the current layers.
+    pub fn push_mod(&mut self, m: slipstream::Mod) -> &mut Self {
+        let layer = self.layers.len();
+        for name in m.file_list() {
+            self.index.insert(name.to_owned(), layer);
+        }
+        self.layers.push(Layer::Mod(m));
+        self
+    }
+    pub fn contains(&self, file: &str) -> bool {
+        self.index.contains_key(file)
+    }
+    pub fn read(&mut self, file: &str) -> io::Result<Vec<u8>> {
+        let layer = *self
+            .index
+            .get(file)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "file not found"))?;
+        let start = (0..=layer)
+            .rev()
+            .find(|&i| self.layers[i].replaces(file))
+            .unwrap_or(0);
+        let mut ret = None;
+        for layer in &mut self.layers[start..=layer] {
+            let replaces = layer.replaces(file);
+            ret = match layer {
+                Layer::Dir { root, prefix } if replaces => {
+                    Some(std::fs::read(root.join(&file[prefix.len()..]))?)
+                }
+                Layer::Data(data) if replaces => Some(data.read(file)?),
+                Layer::Dir { .. } | Layer::Data(_) => ret,
+                Layer::Mod(m) => m.apply(file, ret)?,
+            };
+        }
+        ret.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "file not found"))
+    }
+    /// Check the archive entry for a file. Loose files are always considered valid.
+    pub fn verify_entry(&mut self, file: &str) -> io::Result<()> {
+        let layer = *self
+            .index
+            .get(file)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "file not found"))?;
+        match &mut self.layers[layer] {
+            Layer::Dir { .. } | Layer::Mod(_) => Ok(()),
+            Layer::Data(data) => data.verify_entry(file),
+        }
+    }
+    /// Files starting with `prefix`, sorted by name.
+    pub fn list<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.index
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(|(k, _)| k.as_str())
+            .take_while(move |k| k.starts_with(prefix))
+    }
+    /// Files matching a glob pattern, see [`glob_match`].
+    pub fn glob<'a>(&'a self, pattern: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        let prefix = &pattern[..pattern.find(['*', '?']).unwrap_or(pattern.len())];
+        self.list(prefix).filter(move |k| glob_match(pattern, k))
+    }
+}
+
+/// Match a path against a glob pattern. `?` matches any character except `/`, `*` matches any
+/// number of characters except `/`, `**` matches any number of characters including `/`.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    fn inner(pattern: &[u8], path: &[u8]) -> bool {
+        match pattern {
+            [] => path.is_empty(),
+            [b'*', b'*', rest @ ..] => (0..=path.len()).any(|i| inner(rest, &path[i..])),
+            [b'*', rest @ ..] => (0..=path.len())
+                .take_while(|&i| i == 0 || path[i - 1] != b'/')
+                .any(|i| inner(rest, &path[i..])),
+            [b'?', rest @ ..] => matches!(path, [c, path @ ..] if *c != b'/' && inner(rest, path)),
+            [c, rest @ ..] => matches!(path, [d, path @ ..] if c == d && inner(rest, path)),
+        }
+    }
+    inner(pattern.as_bytes(), path.as_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::{glob_match, Vfs};
+    use crate::{pak::PakBuilder, slipstream::Mod};
+
+    #[test]
+    fn test_glob() {
+        assert!(glob_match("data/*.xml", "data/blueprints.xml"));
+        assert!(!glob_match("data/*.xml", "data/a/blueprints.xml"));
+        assert!(glob_match("data/**.xml", "data/a/blueprints.xml"));
+        assert!(glob_match("data/*luepr*", "data/dlcBlueprints.xml"));
+        assert!(glob_match("img/?.png", "img/a.png"));
+        assert!(!glob_match("img/?.png", "img/ab.png"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("", "a"));
+    }
+
+    #[test]
+    fn test_overlay() {
+        let dir = std::env::temp_dir().join(format!("neuro-ftl-{}-vfs", std::process::id()));
+        std::fs::create_dir_all(dir.join("data/sub")).unwrap();
+        let mut builder = PakBuilder::new();
+        builder
+            .add("data/blueprints.xml", "base")
+            .add("data/text_misc.xml", "base")
+            .add("img/a.png", "base");
+        builder.save(dir.join("ftl.dat")).unwrap();
+        let mut builder = PakBuilder::new();
+        builder
+            .add("data/text_misc.xml", "extra")
+            .add("data/text_blueprints.xml", "extra");
+        builder.save(dir.join("extra.dat")).unwrap();
+        std::fs::write(dir.join("data/blueprints.xml"), "loose").unwrap();
+        std::fs::write(dir.join("data/sub/events.xml"), "loose").unwrap();
+
+        let mut vfs = Vfs::open_game_dir(&dir, [dir.join("extra.dat")]).unwrap();
+        assert_eq!(
+            vfs.list("data/").collect::<Vec<_>>(),
+            [
+                "data/blueprints.xml",
+                "data/sub/events.xml",
+                "data/text_blueprints.xml",
+                "data/text_misc.xml",
+            ]
+        );
+        assert_eq!(
+            vfs.glob("data/*luepr*").collect::<Vec<_>>(),
+            ["data/blueprints.xml", "data/text_blueprints.xml"]
+        );
+        assert_eq!(vfs.list("img/").collect::<Vec<_>>(), ["img/a.png"]);
+        assert_eq!(vfs.read("data/blueprints.xml").unwrap(), b"loose");
+        assert_eq!(vfs.read("data/sub/events.xml").unwrap(), b"loose");
+        assert_eq!(vfs.read("data/text_misc.xml").unwrap(), b"extra");
+        assert_eq!(vfs.read("img/a.png").unwrap(), b"base");
+        assert!(vfs.verify_entry("data/blueprints.xml").is_ok());
+        assert!(vfs.read("data/missing.xml").is_err());
+
+        let path = dir.join("mod.ftl");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("data/text_misc.xml.append", options)
+            .unwrap();
+        zip.write_all(b"<text/>").unwrap();
+        zip.start_file("data/text_mod.xml.append", options).unwrap();
+        zip.write_all(b"<text/>").unwrap();
+        zip.finish().unwrap();
+        vfs.push_mod(Mod::open(&path).unwrap());
+        assert_eq!(vfs.read("data/text_misc.xml").unwrap(), b"extra<text/>");
+        assert_eq!(vfs.read("data/text_mod.xml").unwrap(), b"<text/>");
+        assert_eq!(vfs.read("data/blueprints.xml").unwrap(), b"loose");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
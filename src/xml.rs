@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 pub struct Text {
     #[serde(rename = "@name")]
     pub name: String,
-    #[serde(rename = "@language")]
+    #[serde(rename = "@language", skip_serializing_if = "Option::is_none")]
     pub language: Option<String>,
     #[serde(rename = "$text")]
     pub value: String,
@@ -14,27 +14,26 @@ pub struct Text {
 #[derive(Deserialize, Serialize, Debug, Default, Hash, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct TextString {
-    #[serde(rename = "@id")]
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
-    #[serde(rename = "$text")]
+    #[serde(rename = "$text", skip_serializing_if = "Option::is_none")]
     pub contents: Option<String>,
-    #[serde(rename = "@load")]
+    #[serde(rename = "@load", skip_serializing_if = "Option::is_none")]
     pub load: Option<String>,
 
-    #[serde(rename = "@planet")]
+    #[serde(rename = "@planet", skip_serializing_if = "Option::is_none")]
     pub planet: Option<String>,
-    #[serde(rename = "@back")]
+    #[serde(rename = "@back", skip_serializing_if = "Option::is_none")]
     pub back: Option<String>,
 }
 
 impl TextString {
+    /// The text in the game's language, or the text id if it's missing from the game data
     pub fn to_str(&'static self) -> &'static str {
-        if let Some(id) = &self.load {
-            super::library().text(id).unwrap()
-        } else if let Some(id) = &self.id {
-            super::library().text(id).unwrap()
+        if let Some(id) = self.load.as_ref().or(self.id.as_ref()) {
+            super::library().text(id).unwrap_or(id)
         } else {
-            self.contents.as_ref().unwrap()
+            self.contents.as_deref().unwrap_or_default()
         }
     }
 }
@@ -46,14 +45,16 @@ pub struct Achievement {
     pub id: String,
     pub name: TextString,
     pub desc: TextString,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shortname: Option<TextString>,
     pub img: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ship: Option<String>,
     #[serde(default, rename = "multiDifficulty")]
     pub multi_difficulty: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq)]
 #[serde(rename = "FTL")]
 #[serde(deny_unknown_fields)]
 pub struct XmlText {
@@ -61,7 +62,7 @@ pub struct XmlText {
     pub text: Vec<Text>,
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Default)]
 #[serde(rename = "FTL")]
 #[serde(deny_unknown_fields)]
 pub struct XmlAchievements {
@@ -94,11 +95,13 @@ pub struct Point {
 #[derive(Deserialize, Serialize, Debug, Hash, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct Offsets {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub floor: Option<Point>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cloak: Option<Point>,
 }
 
-#[derive(Deserialize, Serialize, Debug, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Deserialize, Serialize, Debug, Hash, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 #[serde(deny_unknown_fields)]
 pub enum Direction {
@@ -160,7 +163,9 @@ pub struct Explosion {
     pub gib2: Gib,
     pub gib3: Gib,
     pub gib4: Gib,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gib5: Option<Gib>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gib6: Option<Gib>,
 }
 
@@ -219,7 +224,7 @@ pub struct WeaponAnim {
     pub fire_point: Point,
     #[serde(rename = "mountPoint")]
     pub mount_point: Point,
-    #[serde(rename = "chargeImage")]
+    #[serde(rename = "chargeImage", skip_serializing_if = "Option::is_none")]
     pub charge_image: Option<String>,
 }
 
@@ -267,7 +272,7 @@ pub struct Amount {
 pub struct CrewCount {
     #[serde(rename = "@amount")]
     pub amount: u32,
-    #[serde(rename = "@max")]
+    #[serde(rename = "@max", skip_serializing_if = "Option::is_none")]
     pub max: Option<u32>,
     #[serde(rename = "@class")]
     pub class: String,
@@ -280,6 +285,7 @@ const fn true_fn() -> bool {
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct SystemSlot {
+    #[serde(skip_serializing_if = "Option::is_none")]
     direction: Option<Direction>,
     number: i32,
 }
@@ -289,14 +295,14 @@ pub struct SystemSlot {
 pub struct System {
     #[serde(rename = "@power")]
     pub power: u32,
-    #[serde(rename = "@max")]
+    #[serde(rename = "@max", skip_serializing_if = "Option::is_none")]
     pub max: Option<u32>,
     #[serde(rename = "@room")]
     pub room: u32,
     #[serde(default = "true_fn")]
     #[serde(rename = "@start")]
     pub start: bool,
-    #[serde(rename = "@img")]
+    #[serde(rename = "@img", skip_serializing_if = "Option::is_none")]
     pub img: Option<String>,
     #[serde(default, rename = "slot")]
     pub slots: Vec<SystemSlot>,
@@ -307,14 +313,14 @@ pub struct System {
 pub struct ArtillerySystem {
     #[serde(rename = "@power")]
     pub power: u32,
-    #[serde(rename = "@max")]
+    #[serde(rename = "@max", skip_serializing_if = "Option::is_none")]
     pub max: Option<u32>,
     #[serde(rename = "@room")]
     pub room: u32,
     #[serde(default = "true_fn")]
     #[serde(rename = "@start")]
     pub start: bool,
-    #[serde(rename = "@img")]
+    #[serde(rename = "@img", skip_serializing_if = "Option::is_none")]
     pub img: Option<String>,
     #[serde(rename = "@weapon")]
     pub weapon: String,
@@ -325,37 +331,50 @@ pub struct ArtillerySystem {
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct SystemList {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shields: Option<System>,
     pub engines: System,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub oxygen: Option<System>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub weapons: Option<System>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub drones: Option<System>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub medbay: Option<System>,
     pub pilot: System,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sensors: Option<System>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub doors: Option<System>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub teleporter: Option<System>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cloaking: Option<System>,
     #[serde(default)]
     pub artillery: Vec<ArtillerySystem>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub battery: Option<System>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub clonebay: Option<System>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mind: Option<System>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub hacking: Option<System>,
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct DroneList {
-    #[serde(rename = "@load")]
+    #[serde(rename = "@load", skip_serializing_if = "Option::is_none")]
     pub load: Option<String>,
-    #[serde(rename = "@count")]
+    #[serde(rename = "@count", skip_serializing_if = "Option::is_none")]
     pub count: Option<u32>,
     /// This is an option because theres a typo where a droneList is supposed to be a weaponList
-    #[serde(rename = "@drones")]
+    #[serde(rename = "@drones", skip_serializing_if = "Option::is_none")]
     pub drones: Option<u32>,
     /// This is only here because theres a typo where a droneList is supposed to be a weaponList
-    #[serde(rename = "@missiles")]
+    #[serde(rename = "@missiles", skip_serializing_if = "Option::is_none")]
     pub missiles: Option<u32>,
     #[serde(default, rename = "drone")]
     pub blueprints: Vec<BlueprintRef>,
@@ -364,11 +383,11 @@ pub struct DroneList {
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct WeaponList {
-    #[serde(rename = "@count")]
+    #[serde(rename = "@count", skip_serializing_if = "Option::is_none")]
     pub count: Option<u32>,
     #[serde(rename = "@missiles")]
     pub missiles: u32,
-    #[serde(rename = "@load")]
+    #[serde(rename = "@load", skip_serializing_if = "Option::is_none")]
     pub load: Option<String>,
     #[serde(default, rename = "weapon")]
     pub blueprints: Vec<BlueprintRef>,
@@ -393,38 +412,40 @@ pub struct ShipBlueprint {
     #[serde(rename = "@img")]
     pub img: String,
     pub class: TextString,
-    #[serde(rename = "minSector")]
+    #[serde(rename = "minSector", skip_serializing_if = "Option::is_none")]
     pub min_sector: Option<u32>,
-    #[serde(rename = "maxSector")]
+    #[serde(rename = "maxSector", skip_serializing_if = "Option::is_none")]
     pub max_sector: Option<u32>,
-    #[serde(rename = "name")]
+    #[serde(rename = "name", skip_serializing_if = "Option::is_none")]
     pub title: Option<TextString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub unlock: Option<TextString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub desc: Option<TextString>,
     #[serde(rename = "systemList")]
     pub system_list: SystemList,
     #[serde(default, rename = "droneList")]
     pub drone_list: Vec<DroneList>,
-    #[serde(rename = "droneSlots")]
+    #[serde(rename = "droneSlots", skip_serializing_if = "Option::is_none")]
     pub drone_slots: Option<u32>,
-    #[serde(rename = "weaponList")]
+    #[serde(rename = "weaponList", skip_serializing_if = "Option::is_none")]
     pub weapon_list: Option<WeaponList>,
-    #[serde(rename = "weaponSlots")]
+    #[serde(rename = "weaponSlots", skip_serializing_if = "Option::is_none")]
     pub weapon_slots: Option<u32>,
     pub health: Amount,
     #[serde(rename = "maxPower")]
     pub max_power: Amount,
     #[serde(default, rename = "crewCount")]
     pub crew_count: Vec<CrewCount>,
-    #[serde(rename = "boardingAI")]
+    #[serde(rename = "boardingAI", skip_serializing_if = "Option::is_none")]
     pub boarding_ai: Option<String>,
     #[serde(default)]
     pub aug: Vec<BlueprintRef>,
-    #[serde(rename = "cloakImage")]
+    #[serde(rename = "cloakImage", skip_serializing_if = "Option::is_none")]
     pub cloak_image: Option<String>,
-    #[serde(rename = "shieldImage")]
+    #[serde(rename = "shieldImage", skip_serializing_if = "Option::is_none")]
     pub shield_image: Option<String>,
-    #[serde(rename = "floorImage")]
+    #[serde(rename = "floorImage", skip_serializing_if = "Option::is_none")]
     pub floor_image: Option<String>,
 }
 
@@ -485,7 +506,7 @@ pub struct CrewBlueprint {
     pub rarity: u32,
     #[serde(rename = "powerList")]
     pub power_list: PowerList,
-    #[serde(rename = "colorList")]
+    #[serde(rename = "colorList", skip_serializing_if = "Option::is_none")]
     pub color_list: Option<ColorList>,
 }
 
@@ -565,20 +586,25 @@ pub struct WeaponBlueprint {
     pub title: TextString,
     #[serde(default)]
     pub short: Vec<TextString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub desc: Option<TextString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tooltip: Option<TextString>,
-    #[serde(rename = "flavorType")]
+    #[serde(rename = "flavorType", skip_serializing_if = "Option::is_none")]
     pub flavor_type: Option<TextString>,
     #[serde(default)]
     pub tip: Vec<TextString>,
     // damage.i_damage
     pub damage: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub missiles: Option<u32>,
     // shots
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub shots: Option<u32>,
     // damage.i_shield_piercing
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sp: Option<u32>,
-    #[serde(rename = "chargeLevels")]
+    #[serde(rename = "chargeLevels", skip_serializing_if = "Option::is_none")]
     pub charge_levels: Option<u32>,
     // damage.fire_chance
     #[serde(rename = "fireChance")]
@@ -587,55 +613,71 @@ pub struct WeaponBlueprint {
     #[serde(rename = "breachChance")]
     pub breach_chance: u32,
     // cooldown
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cooldown: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub power: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cost: Option<u32>,
     // color
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<Rgb>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bp: Option<u32>,
     pub rarity: u32,
     // damage.i_ion_damage
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ion: Option<u32>,
     // damage.b_hull_buster
-    #[serde(rename = "hullBust")]
+    #[serde(rename = "hullBust", skip_serializing_if = "Option::is_none")]
     pub hull_bust: Option<bool>,
     // damage.i_pers_damage
-    #[serde(rename = "persDamage")]
+    #[serde(rename = "persDamage", skip_serializing_if = "Option::is_none")]
     pub pers_damage: Option<i32>,
     // damage.b_lockdown
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lockdown: Option<bool>,
     // damage.i_system_damage
-    #[serde(rename = "sysDamage")]
+    #[serde(rename = "sysDamage", skip_serializing_if = "Option::is_none")]
     pub sys_damage: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub speed: Option<u32>,
     // damage.stun_chance
-    #[serde(rename = "stunChance")]
+    #[serde(rename = "stunChance", skip_serializing_if = "Option::is_none")]
     pub stun_chance: Option<u32>,
-    #[serde(rename = "iconImage")]
+    #[serde(rename = "iconImage", skip_serializing_if = "Option::is_none")]
     pub icon_image: Option<String>,
     // length
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub length: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub image: Option<String>,
     #[serde(rename = "launchSounds")]
     pub launch_sounds: Sounds,
-    #[serde(rename = "hitShipSounds")]
+    #[serde(rename = "hitShipSounds", skip_serializing_if = "Option::is_none")]
     pub hit_ship_sounds: Option<Sounds>,
-    #[serde(rename = "hitShieldSounds")]
+    #[serde(rename = "hitShieldSounds", skip_serializing_if = "Option::is_none")]
     pub hit_shield_sounds: Option<Sounds>,
-    #[serde(rename = "missSounds")]
+    #[serde(rename = "missSounds", skip_serializing_if = "Option::is_none")]
     pub miss_sounds: Option<Sounds>,
     #[serde(rename = "weaponArt")]
     pub weapon_art: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub explosion: Option<String>,
     #[serde(default)]
     pub locked: bool,
     #[serde(default)]
     pub drone_targetable: Vec<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub radius: Option<u32>,
     // damage.i_stun
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stun: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub boost: Option<Boost>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub spin: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub projectiles: Option<Projectiles>,
 }
 
@@ -648,25 +690,33 @@ pub struct DroneBlueprint {
     pub noloc: bool,
     #[serde(rename = "type")]
     pub type_: DroneType,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tip: Option<TextString>,
     pub title: TextString,
     pub short: TextString,
     pub desc: TextString,
     pub power: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cooldown: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub dodge: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub speed: Option<u32>,
     pub cost: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bp: Option<u32>,
-    #[serde(rename = "droneImage")]
+    #[serde(rename = "droneImage", skip_serializing_if = "Option::is_none")]
     pub drone_image: Option<String>,
-    #[serde(rename = "iconImage")]
+    #[serde(rename = "iconImage", skip_serializing_if = "Option::is_none")]
     pub icon_image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub image: Option<String>,
-    #[serde(rename = "weaponBlueprint")]
+    #[serde(rename = "weaponBlueprint", skip_serializing_if = "Option::is_none")]
     pub weapon_blueprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub target: Option<DroneTarget>,
     pub rarity: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub level: Option<u32>,
     #[serde(default)]
     pub locked: bool,
@@ -680,9 +730,11 @@ pub struct AugBlueprint {
     pub title: TextString,
     pub desc: TextString,
     pub cost: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bp: Option<u32>,
     pub rarity: u32,
     pub stackable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<f64>,
 }
 
@@ -699,7 +751,7 @@ pub struct ItemBlueprint {
     pub rarity: u32,
 }
 
-#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq)]
 #[serde(rename = "FTL")]
 #[serde(deny_unknown_fields)]
 pub struct XmlBlueprints {
@@ -768,19 +820,23 @@ pub struct WeaponOverride {
 pub struct Ship {
     #[serde(rename = "@name")]
     pub name: String,
-    #[serde(rename = "@blueprint")]
+    #[serde(rename = "@blueprint", skip_serializing_if = "Option::is_none")]
     pub blueprint: Option<String>,
-    #[serde(rename = "@auto_blueprint")]
+    #[serde(rename = "@auto_blueprint", skip_serializing_if = "Option::is_none")]
     pub auto_blueprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub surrender: Option<Event>,
     #[serde(default)]
     pub escape: Vec<Event>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gotaway: Option<Event>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub destroyed: Option<Event>,
-    #[serde(rename = "deadCrew")]
+    #[serde(rename = "deadCrew", skip_serializing_if = "Option::is_none")]
     pub dead_crew: Option<Event>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub crew: Option<Crew>,
-    #[serde(rename = "weaponOverride")]
+    #[serde(rename = "weaponOverride", skip_serializing_if = "Option::is_none")]
     pub weapon_override: Option<WeaponOverride>,
 }
 
@@ -807,11 +863,11 @@ where
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct ShipRef {
-    #[serde(rename = "@load")]
+    #[serde(rename = "@load", skip_serializing_if = "Option::is_none")]
     pub load: Option<String>,
     #[serde(default, rename = "@hostile", deserialize_with = "case_insensitive_de")]
     pub hostile: bool,
-    #[serde(flatten)]
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
     pub ship: Option<Box<Ship>>,
 }
 
@@ -820,7 +876,7 @@ pub struct ShipRef {
 pub struct Environment {
     #[serde(rename = "@type")]
     pub type_: EnvironmentType,
-    #[serde(rename = "@target")]
+    #[serde(rename = "@target", skip_serializing_if = "Option::is_none")]
     pub target: Option<Target>,
 }
 
@@ -917,7 +973,7 @@ pub struct Item {
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct Choice {
-    #[serde(rename = "@req")]
+    #[serde(rename = "@req", skip_serializing_if = "Option::is_none")]
     pub req: Option<String>,
     #[serde(default, rename = "@hidden")]
     pub hidden: bool,
@@ -925,18 +981,19 @@ pub struct Choice {
     pub hiiden: bool,
     #[serde(default, rename = "@blue")]
     pub blue: bool,
-    #[serde(rename = "@lvl")]
+    #[serde(rename = "@lvl", skip_serializing_if = "Option::is_none")]
     pub lvl: Option<u32>,
-    #[serde(rename = "@max_lvl")]
+    #[serde(rename = "@max_lvl", skip_serializing_if = "Option::is_none")]
     pub max_lvl: Option<u32>,
-    #[serde(rename = "@min_level")]
+    #[serde(rename = "@min_level", skip_serializing_if = "Option::is_none")]
     pub min_level: Option<u32>,
-    #[serde(rename = "@max_group")]
+    #[serde(rename = "@max_group", skip_serializing_if = "Option::is_none")]
     pub max_group: Option<u32>,
     pub text: TextString,
     pub event: Event,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub choice: Option<Box<Choice>>,
-    #[serde(rename = "autoReward")]
+    #[serde(rename = "autoReward", skip_serializing_if = "Option::is_none")]
     pub auto_reward: Option<AutoReward>,
 }
 
@@ -1030,36 +1087,36 @@ pub struct AutoReward {
 pub struct CrewMember {
     #[serde(rename = "@amount")]
     pub amount: i32,
-    #[serde(rename = "@class")]
+    #[serde(rename = "@class", skip_serializing_if = "Option::is_none")]
     pub class: Option<String>,
-    #[serde(rename = "@type")]
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
     pub type_: Option<String>,
-    #[serde(rename = "@id")]
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
-    #[serde(rename = "@weapons")]
+    #[serde(rename = "@weapons", skip_serializing_if = "Option::is_none")]
     pub weapons: Option<u32>,
-    #[serde(rename = "@shields")]
+    #[serde(rename = "@shields", skip_serializing_if = "Option::is_none")]
     pub shields: Option<u32>,
-    #[serde(rename = "@pilot")]
+    #[serde(rename = "@pilot", skip_serializing_if = "Option::is_none")]
     pub pilot: Option<u32>,
-    #[serde(rename = "@engines")]
+    #[serde(rename = "@engines", skip_serializing_if = "Option::is_none")]
     pub engines: Option<u32>,
-    #[serde(rename = "@combat")]
+    #[serde(rename = "@combat", skip_serializing_if = "Option::is_none")]
     pub combat: Option<u32>,
-    #[serde(rename = "@repair")]
+    #[serde(rename = "@repair", skip_serializing_if = "Option::is_none")]
     pub repair: Option<u32>,
-    #[serde(rename = "@all_skills")]
+    #[serde(rename = "@all_skills", skip_serializing_if = "Option::is_none")]
     pub all_skills: Option<u32>,
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct CrewMember2 {
-    #[serde(rename = "@class")]
+    #[serde(rename = "@class", skip_serializing_if = "Option::is_none")]
     pub class: Option<String>,
-    #[serde(rename = "@prop")]
+    #[serde(rename = "@prop", skip_serializing_if = "Option::is_none")]
     pub prop: Option<f64>,
-    #[serde(rename = "@type")]
+    #[serde(rename = "@type", skip_serializing_if = "Option::is_none")]
     pub type_: Option<String>,
 }
 
@@ -1070,7 +1127,7 @@ pub struct Boarders {
     pub min: u32,
     #[serde(rename = "@max")]
     pub max: u32,
-    #[serde(rename = "@class")]
+    #[serde(rename = "@class", skip_serializing_if = "Option::is_none")]
     pub class: Option<String>,
     #[serde(default, rename = "@breach")]
     pub breach: bool,
@@ -1079,7 +1136,7 @@ pub struct Boarders {
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct RemoveCrew {
-    #[serde(rename = "@class")]
+    #[serde(rename = "@class", skip_serializing_if = "Option::is_none")]
     pub class: Option<String>,
     pub clone: bool,
     pub text: TextString,
@@ -1101,7 +1158,7 @@ pub struct Image {
     pub w: u32,
     #[serde(rename = "@h")]
     pub h: u32,
-    #[serde(rename = "$text")]
+    #[serde(rename = "$text", skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
 }
 
@@ -1110,7 +1167,7 @@ pub struct Image {
 pub struct ImageList {
     #[serde(rename = "@name")]
     pub name: String,
-    #[serde(rename = "@ui")]
+    #[serde(rename = "@ui", skip_serializing_if = "Option::is_none")]
     pub ui: Option<String>,
     pub img: Vec<Image>,
 }
@@ -1118,9 +1175,9 @@ pub struct ImageList {
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct EventImage {
-    #[serde(rename = "@back")]
+    #[serde(rename = "@back", skip_serializing_if = "Option::is_none")]
     pub back: Option<String>,
-    #[serde(rename = "@planet")]
+    #[serde(rename = "@planet", skip_serializing_if = "Option::is_none")]
     pub planet: Option<String>,
 }
 
@@ -1129,9 +1186,9 @@ pub struct EventImage {
 pub struct Damage {
     #[serde(rename = "@amount")]
     pub amount: i32,
-    #[serde(rename = "@system")]
+    #[serde(rename = "@system", skip_serializing_if = "Option::is_none")]
     pub system: Option<SystemName>,
-    #[serde(rename = "@effect")]
+    #[serde(rename = "@effect", skip_serializing_if = "Option::is_none")]
     pub effect: Option<DamageEffect>,
 }
 
@@ -1161,7 +1218,7 @@ pub struct Status {
     pub target: Target,
     #[serde(rename = "@system")]
     pub system: SystemName,
-    #[serde(rename = "@amount")]
+    #[serde(rename = "@amount", skip_serializing_if = "Option::is_none")]
     pub amount: Option<u32>,
 }
 
@@ -1177,66 +1234,91 @@ pub struct Upgrade {
 #[derive(Deserialize, Serialize, Debug, Default, Hash, Eq, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct UnlockShip {
-    #[serde(rename = "@id")]
+    #[serde(rename = "@id", skip_serializing_if = "Option::is_none")]
     pub id: Option<u32>,
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
 #[serde(deny_unknown_fields)]
 pub struct Event {
-    #[serde(rename = "@load")]
+    #[serde(rename = "@load", skip_serializing_if = "Option::is_none")]
     pub load: Option<String>,
-    #[serde(rename = "@chance")]
+    #[serde(rename = "@chance", skip_serializing_if = "Option::is_none")]
     pub chance: Option<f64>,
-    #[serde(rename = "@timer")]
+    #[serde(rename = "@timer", skip_serializing_if = "Option::is_none")]
     pub timer: Option<u32>,
-    #[serde(rename = "@min")]
+    #[serde(rename = "@min", skip_serializing_if = "Option::is_none")]
     pub min: Option<u32>,
-    #[serde(rename = "@max")]
+    #[serde(rename = "@max", skip_serializing_if = "Option::is_none")]
     pub max: Option<u32>,
-    #[serde(rename = "@loss1")]
+    #[serde(rename = "@loss1", skip_serializing_if = "Option::is_none")]
     pub loss1: Option<String>,
 
-    #[serde(rename = "@name")]
+    #[serde(rename = "@name", skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
-    #[serde(rename = "@unique")]
+    #[serde(rename = "@unique", skip_serializing_if = "Option::is_none")]
     pub unique: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub text: Option<TextString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub img: Option<EventImage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ship: Option<ShipRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub environment: Option<Environment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub item_modify: Option<ItemModify>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub weapon: Option<BlueprintRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub drone: Option<BlueprintRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub augment: Option<BlueprintRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub remove: Option<BlueprintRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub quest: Option<Quest>,
     #[serde(default)]
     pub status: Vec<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub fleet: Option<String>,
-    #[serde(rename = "modifyPursuit")]
+    #[serde(rename = "modifyPursuit", skip_serializing_if = "Option::is_none")]
     pub modiy_pursuit: Option<Amount>,
-    #[serde(rename = "crewMember")]
+    #[serde(rename = "crewMember", skip_serializing_if = "Option::is_none")]
     pub crew_member: Option<CrewMember>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub boarders: Option<Boarders>,
     #[serde(default)]
     pub damage: Vec<Damage>,
-    #[serde(rename = "removeCrew")]
+    #[serde(rename = "removeCrew", skip_serializing_if = "Option::is_none")]
     pub remove_crew: Option<RemoveCrew>,
-    #[serde(rename = "autoReward")]
+    #[serde(rename = "autoReward", skip_serializing_if = "Option::is_none")]
     pub auto_reward: Option<AutoReward>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub store: Option<()>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub repair: Option<()>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub upgrade: Option<Upgrade>,
-    #[serde(default, rename = "distressBeacon")]
+    #[serde(
+        default,
+        rename = "distressBeacon",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub distress_beacon: Option<()>,
-    #[serde(default, rename = "secretSector")]
+    #[serde(
+        default,
+        rename = "secretSector",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub secret_sector: Option<()>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reveal_map: Option<()>,
-    #[serde(rename = "unlockShip")]
+    #[serde(rename = "unlockShip", skip_serializing_if = "Option::is_none")]
     pub unlock_ship: Option<()>,
     #[serde(default, rename = "choice")]
     pub choices: Vec<Choice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub event: Option<Box<Event>>,
 }
 
@@ -1270,6 +1352,62 @@ pub struct EventCounts {
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SectorType {
+    #[serde(rename = "@name")]
+    pub name: String,
+    #[serde(default, rename = "sector")]
+    pub sectors: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Rarity {
+    #[serde(rename = "@name")]
+    pub name: String,
+    /// 1 is the most common, 5 the least, 0 means it never appears
+    #[serde(rename = "@rarity")]
+    pub rarity: u32,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RarityList {
+    #[serde(default, rename = "blueprint")]
+    pub blueprints: Vec<Rarity>,
+}
+
+// no deny_unknown_fields: names and music aren't modelled
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SectorDescription {
+    #[serde(rename = "@name")]
+    pub name: String,
+    #[serde(rename = "@minSector", skip_serializing_if = "Option::is_none")]
+    pub min_sector: Option<u32>,
+    #[serde(default, rename = "@unique", deserialize_with = "case_insensitive_de")]
+    pub unique: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_event: Option<String>,
+    #[serde(default, rename = "event")]
+    pub events: Vec<EventCount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rarity_list: Option<RarityList>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq)]
+#[serde(rename = "FTL")]
+#[serde(deny_unknown_fields)]
+pub struct XmlSectors {
+    #[serde(default, rename = "sectorType")]
+    pub sector_types: Vec<SectorType>,
+    #[serde(default, rename = "sectorDescription")]
+    pub sector_descriptions: Vec<SectorDescription>,
+    #[serde(default, rename = "eventCounts")]
+    pub event_counts: Vec<EventCounts>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, PartialEq)]
 #[serde(rename = "FTL")]
 #[serde(deny_unknown_fields)]
 pub struct XmlEvents {
@@ -1292,7 +1430,7 @@ pub struct Sound {
     pub volume: u32,
     #[serde(default, rename = "@loop")]
     pub loop_: bool,
-    #[serde(rename = "@count")]
+    #[serde(rename = "@count", skip_serializing_if = "Option::is_none")]
     pub count: Option<u32>,
     #[serde(rename = "$text")]
     pub path: String,
@@ -1356,8 +1494,103 @@ pub struct XmlSounds {
     pub event_damage: Sound,
 }
 
+/// Serialize a game data file, e.g. [`XmlBlueprints`], in the format the game's own files use.
+pub fn to_string<T: Serialize>(value: &T) -> Result<String, quick_xml::SeError> {
+    let mut ret = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n".to_owned();
+    let mut ser = quick_xml::se::Serializer::new(&mut ret);
+    ser.indent(' ', 4);
+    value.serialize(ser)?;
+    ret.push('\n');
+    Ok(ret)
+}
+
+pub mod lenient;
+#[cfg(test)]
+mod random;
+
 #[cfg(test)]
 mod test {
+    use super::random;
+
+    fn assert_roundtrip<T>(x: &T)
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let text = super::to_string(x).unwrap();
+        match quick_xml::de::from_str::<T>(&text) {
+            Ok(y) => assert_eq!(x, &y, "{text}"),
+            Err(err) => panic!("{err}\n{text}"),
+        }
+    }
+
+    #[test]
+    fn test_sectors() {
+        let text = r#"
+            <?xml version="1.0" encoding="utf-8"?>
+            <FTL>
+            <sectorType name="CIVILIAN">
+                <sector>CIVILIAN_SECTOR</sector>
+                <sector>ENGI_SECTOR</sector>
+            </sectorType>
+            <sectorDescription name="CIVILIAN_SECTOR" minSector="0" unique="false">
+                <nameList><name id="sec_CIVILIAN_SECTOR_name"/></nameList>
+                <trackList><track>civil</track></trackList>
+                <startEvent>START_BEACON</startEvent>
+                <event name="NEUTRAL_CIVILIAN" min="4" max="6"/>
+                <event name="STORE" min="1" max="2"/>
+                <rarityList>
+                    <blueprint name="LASER_BURST_1" rarity="1"/>
+                </rarityList>
+            </sectorDescription>
+            <eventCounts sector="0">
+                <event name="ITEMS" min="1" max="1"/>
+            </eventCounts>
+            </FTL>
+        "#;
+        let x: super::XmlSectors = quick_xml::de::from_str(text).unwrap();
+        assert_eq!(
+            x.sector_types[0].sectors,
+            ["CIVILIAN_SECTOR", "ENGI_SECTOR"]
+        );
+        let desc = &x.sector_descriptions[0];
+        assert_eq!(desc.start_event.as_deref(), Some("START_BEACON"));
+        assert_eq!(desc.events.len(), 2);
+        assert_eq!(desc.rarity_list.as_ref().unwrap().blueprints[0].rarity, 1);
+        assert_eq!(x.event_counts[0].events[0].name, "ITEMS");
+        assert_roundtrip(&x);
+    }
+
+    /// Round trip values made up from a fixed range of seeds, a failure names the seed so it can
+    /// be reproduced with [`random::value`]
+    fn assert_roundtrip_random<T>()
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        for seed in 0..256 {
+            let x = random::value::<T>(seed);
+            let text = super::to_string(&x).unwrap();
+            match quick_xml::de::from_str::<T>(&text) {
+                Ok(y) => assert_eq!(x, y, "seed {seed}: {text}"),
+                Err(err) => panic!("seed {seed}: {err}\n{text}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_text() {
+        assert_roundtrip_random::<super::XmlText>();
+    }
+
+    #[test]
+    fn test_roundtrip_blueprints() {
+        assert_roundtrip_random::<super::XmlBlueprints>();
+    }
+
+    #[test]
+    fn test_roundtrip_events() {
+        assert_roundtrip_random::<super::XmlEvents>();
+    }
+
     #[test]
     fn test_text() {
         let text = r#"
@@ -1368,6 +1601,7 @@ mod test {
             </FTL>
         "#;
         let x: super::XmlText = quick_xml::de::from_str(text).unwrap();
+        assert_roundtrip(&x);
         assert_eq!(
             &x.text,
             &[
@@ -1574,7 +1808,8 @@ mod test {
             </FTL>
         "#;
 
-        let _: super::XmlBlueprints = quick_xml::de::from_str(text).unwrap();
+        let x: super::XmlBlueprints = quick_xml::de::from_str(text).unwrap();
+        assert_roundtrip(&x);
     }
 
     #[test]
@@ -1855,9 +2090,12 @@ mod test {
         ];
         for elem in text {
             let text = header.to_owned() + elem + trailer;
-            if let Err(err) = quick_xml::de::from_str::<super::XmlEvents>(&text) {
-                eprintln!("{text}");
-                panic!("{err}");
+            match quick_xml::de::from_str::<super::XmlEvents>(&text) {
+                Ok(x) => assert_roundtrip(&x),
+                Err(err) => {
+                    eprintln!("{text}");
+                    panic!("{err}");
+                }
             }
         }
     }
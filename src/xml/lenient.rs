@@ -0,0 +1,402 @@
+//! Reading game data that isn't quite valid XML. Vanilla files have a few typos in them (stray
+//! characters after tags, `1.f` floats), and mods can have anything, so instead of rejecting the
+//! whole file this repairs what it can, and skips the top-level elements that still don't parse.
+use std::{borrow::Cow, fmt};
+
+use quick_xml::{
+    events::{BytesEnd, BytesText, Event},
+    Reader, Writer,
+};
+use serde::de::DeserializeOwned;
+
+use super::{XmlAchievements, XmlBlueprints, XmlEvents, XmlSectors, XmlText};
+
+/// A problem found in a file, along with whether it was repaired or the data was skipped
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub file: String,
+    /// 1-based
+    pub line: usize,
+    /// [`log::Level::Info`] if the problem was repaired, [`log::Level::Warn`] if data was lost
+    pub level: log::Level,
+    pub issue: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.file, self.line, self.issue)
+    }
+}
+
+/// A root element made up of lists of elements, which can be parsed one element at a time
+pub trait Root: DeserializeOwned + Default {
+    fn append(&mut self, other: Self);
+}
+
+macro_rules! impl_root {
+    ($($ty:ty { $($field:ident),* $(,)? })*) => {
+        $(impl Root for $ty {
+            fn append(&mut self, other: Self) {
+                $(self.$field.extend(other.$field);)*
+            }
+        })*
+    };
+}
+
+impl_root! {
+    XmlText { text }
+    XmlAchievements { achievements }
+    XmlBlueprints {
+        blueprint_lists,
+        crew_blueprints,
+        system_blueprints,
+        weapon_blueprints,
+        drone_blueprints,
+        aug_blueprints,
+        item_blueprints,
+        ship_blueprints,
+    }
+    XmlSectors { sector_types, sector_descriptions, event_counts }
+    XmlEvents { events, event_lists, text_lists, ships, event_counts }
+}
+
+/// A repaired file, always wrapped in a single `<FTL>` root element
+pub struct Document {
+    file: String,
+    text: String,
+    /// byte range in `text` and line in the original file of every top-level element
+    elements: Vec<(usize, usize, usize)>,
+}
+
+struct Lines(Vec<usize>);
+
+impl Lines {
+    fn new(text: &str) -> Self {
+        Self(text.match_indices('\n').map(|x| x.0).collect())
+    }
+    fn line(&self, pos: usize) -> usize {
+        self.0.partition_point(|x| *x < pos) + 1
+    }
+}
+
+/// `1.f` -> `1.0`, `-2.5f` -> `-2.5`
+fn fix_float(x: &str) -> Option<String> {
+    let num = x.strip_suffix('f')?;
+    let (int, frac) = num.split_once('.')?;
+    let int = int.strip_prefix('-').unwrap_or(int);
+    if int.is_empty()
+        || !int.bytes().all(|x| x.is_ascii_digit())
+        || !frac.bytes().all(|x| x.is_ascii_digit())
+    {
+        return None;
+    }
+    Some(if frac.is_empty() {
+        format!("{num}0")
+    } else {
+        num.to_owned()
+    })
+}
+
+/// Escape `&`s that don't start an entity
+fn fix_ampersands(x: &str) -> Option<String> {
+    if quick_xml::escape::unescape(x).is_ok() {
+        return None;
+    }
+    let mut ret = String::with_capacity(x.len());
+    for (i, part) in x.split('&').enumerate() {
+        if i > 0 {
+            let valid = part.split_once(';').is_some_and(|(name, _)| {
+                matches!(name, "amp" | "lt" | "gt" | "quot" | "apos")
+                    || name.strip_prefix("#x").map_or_else(
+                        || {
+                            name.strip_prefix('#')
+                                .is_some_and(|x| x.parse::<u32>().is_ok())
+                        },
+                        |x| u32::from_str_radix(x, 16).is_ok(),
+                    )
+            });
+            ret.push_str(if valid { "&" } else { "&amp;" });
+        }
+        ret.push_str(part);
+    }
+    Some(ret)
+}
+
+/// Repair a file's contents, adding what was wrong with it to `diagnostics`
+pub fn preprocess(file: &str, contents: &[u8], diagnostics: &mut Vec<Diagnostic>) -> Document {
+    let mut diag = |line: usize, level: log::Level, issue: String| {
+        diagnostics.push(Diagnostic {
+            file: file.to_owned(),
+            line,
+            level,
+            issue,
+        })
+    };
+    let contents = match std::str::from_utf8(contents) {
+        Ok(x) => Cow::Borrowed(x),
+        Err(err) => {
+            let valid = &contents[..err.valid_up_to()];
+            let line = valid.iter().filter(|x| **x == b'\n').count() + 1;
+            diag(line, log::Level::Info, "invalid UTF-8".to_owned());
+            String::from_utf8_lossy(contents)
+        }
+    };
+    let lines = Lines::new(&contents);
+    let mut reader = Reader::from_str(&contents);
+    let config = reader.config_mut();
+    config.check_end_names = false;
+    config.allow_unmatched_ends = true;
+    let mut writer = Writer::new(b"<FTL>".to_vec());
+    let mut elements = Vec::new();
+    // open elements, the root `<FTL>` elements aren't written since the output has its own
+    let mut stack = Vec::<(String, bool)>::new();
+    let mut depth = 0usize;
+    let mut element_start = (0, 0);
+    let mut after_child = false;
+    loop {
+        let pos = reader.buffer_position() as usize;
+        let line = lines.line(pos);
+        let event = match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(event) => event,
+            Err(err) => {
+                let line = lines.line(reader.error_position() as usize);
+                diag(
+                    line,
+                    log::Level::Warn,
+                    format!("{err}, ignoring the rest of the file"),
+                );
+                break;
+            }
+        };
+        let out_pos = writer.get_ref().len();
+        match event {
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                after_child = false;
+                if stack.is_empty() && name == "FTL" {
+                    stack.push((name, false));
+                    continue;
+                }
+                if depth == 0 {
+                    element_start = (out_pos, line);
+                }
+                writer.write_event(Event::Start(e)).unwrap();
+                stack.push((name, true));
+                depth += 1;
+            }
+            Event::End(e) => {
+                let end_name = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                let Some(i) = stack.iter().rposition(|x| x.0 == end_name) else {
+                    diag(
+                        line,
+                        log::Level::Info,
+                        format!("removed unexpected </{end_name}>"),
+                    );
+                    continue;
+                };
+                for (name, written) in stack.drain(i..).rev() {
+                    if name != end_name {
+                        diag(line, log::Level::Info, format!("closed unclosed <{name}>"));
+                    }
+                    if written {
+                        writer.write_event(Event::End(BytesEnd::new(name))).unwrap();
+                        depth -= 1;
+                        if depth == 0 {
+                            elements.push((
+                                element_start.0,
+                                writer.get_ref().len(),
+                                element_start.1,
+                            ));
+                        }
+                    }
+                }
+                after_child = true;
+            }
+            Event::Empty(e) => {
+                writer.write_event(Event::Empty(e)).unwrap();
+                if depth == 0 {
+                    elements.push((out_pos, writer.get_ref().len(), line));
+                }
+                after_child = true;
+            }
+            Event::Text(e) => {
+                let raw = String::from_utf8_lossy(&e);
+                let text = raw.trim();
+                if text.is_empty() {
+                    writer.write_event(Event::Text(e)).unwrap();
+                } else if depth == 0 || after_child {
+                    // e.g. `</event>-`, text is never mixed with elements in game data
+                    let line = lines.line(pos + raw.len() - raw.trim_start().len());
+                    diag(
+                        line,
+                        log::Level::Info,
+                        format!("removed stray text {text:?}"),
+                    );
+                } else if let Some(fixed) = fix_float(text) {
+                    diag(
+                        line,
+                        log::Level::Info,
+                        format!("replaced {text:?} with {fixed:?}"),
+                    );
+                    writer
+                        .write_event(Event::Text(BytesText::from_escaped(fixed)))
+                        .unwrap();
+                } else if let Some(fixed) = fix_ampersands(&raw) {
+                    diag(line, log::Level::Info, "escaped unescaped &".to_owned());
+                    writer
+                        .write_event(Event::Text(BytesText::from_escaped(fixed)))
+                        .unwrap();
+                } else {
+                    writer.write_event(Event::Text(e)).unwrap();
+                }
+            }
+            Event::CData(e) => {
+                writer.write_event(Event::CData(e)).unwrap();
+            }
+            // the output gets its own declaration-less root, and comments aren't needed
+            Event::Decl(_) | Event::PI(_) | Event::DocType(_) | Event::Comment(_) => {}
+            Event::Eof => unreachable!(),
+        }
+    }
+    let line = lines.line(contents.len());
+    for (name, written) in stack.into_iter().rev() {
+        if written {
+            diag(line, log::Level::Info, format!("closed unclosed <{name}>"));
+            writer.write_event(Event::End(BytesEnd::new(name))).unwrap();
+            depth -= 1;
+            if depth == 0 {
+                elements.push((element_start.0, writer.get_ref().len(), element_start.1));
+            }
+        }
+    }
+    let mut text = writer.into_inner();
+    text.extend_from_slice(b"</FTL>");
+    Document {
+        file: file.to_owned(),
+        // only valid UTF-8 was written
+        text: String::from_utf8(text).unwrap(),
+        elements,
+    }
+}
+
+impl Document {
+    /// The repaired file
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Name of the first top-level element, which tells what kind of file this is
+    pub fn first_element(&self) -> Option<&str> {
+        let &(start, end, _) = self.elements.first()?;
+        self.text[start + 1..end]
+            .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .next()
+    }
+    /// Parse the whole document at once
+    pub fn parse_all<T: DeserializeOwned>(&self) -> Result<T, quick_xml::DeError> {
+        quick_xml::de::from_str(&self.text)
+    }
+    /// Parse the document, skipping the top-level elements that can't be parsed
+    pub fn parse<T: Root>(&self, diagnostics: &mut Vec<Diagnostic>) -> T {
+        if let Ok(ret) = self.parse_all() {
+            return ret;
+        }
+        let mut ret = T::default();
+        for &(start, end, line) in &self.elements {
+            match quick_xml::de::from_str(&format!("<FTL>{}</FTL>", &self.text[start..end])) {
+                Ok(x) => ret.append(x),
+                Err(err) => diagnostics.push(Diagnostic {
+                    file: self.file.clone(),
+                    line,
+                    level: log::Level::Warn,
+                    issue: format!("skipped element that failed to parse: {err}"),
+                }),
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fix_ampersands, fix_float, preprocess};
+    use crate::xml::{XmlEvents, XmlText};
+
+    #[test]
+    fn test_fixes() {
+        assert_eq!(fix_float("1.f").as_deref(), Some("1.0"));
+        assert_eq!(fix_float("-2.5f").as_deref(), Some("-2.5"));
+        assert_eq!(fix_float("1.0"), None);
+        assert_eq!(fix_float(".f"), None);
+        assert_eq!(fix_float("leaf"), None);
+        assert_eq!(fix_ampersands("a &amp; b"), None);
+        assert_eq!(
+            fix_ampersands("a & b &#33; &lt;").as_deref(),
+            Some("a &amp; b &#33; &lt;")
+        );
+    }
+
+    #[test]
+    fn test_preprocess() {
+        let text = "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<FTL>
+<!-- a comment -->
+<event name=\"A\">
+    <text>Hello & welcome</text>
+</event>-
+<event name=\"B\">
+    <text>Two</text>
+    <ship load=\"SHIP\" hostile=\"true\"/>.
+</event>
+<event name=\"C\" unknown=\"attribute\">
+    <text>Three</text>
+</event>
+<event name=\"D\">
+    <text>Four</text>
+</FTL>";
+        let mut diagnostics = Vec::new();
+        let doc = preprocess("data/events.xml", text.as_bytes(), &mut diagnostics);
+        let issues: Vec<_> = diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            issues,
+            [
+                "data/events.xml:5: escaped unescaped &",
+                "data/events.xml:6: removed stray text \"-\"",
+                "data/events.xml:9: removed stray text \".\"",
+                "data/events.xml:16: closed unclosed <event>",
+            ]
+        );
+        assert!(doc.parse_all::<XmlEvents>().is_err());
+        diagnostics.clear();
+        let events: XmlEvents = doc.parse(&mut diagnostics);
+        let names: Vec<_> = events.events.iter().map(|x| x.name.as_deref()).collect();
+        assert_eq!(names, [Some("A"), Some("B"), Some("D")]);
+        assert_eq!(
+            events.events[0].text.as_ref().unwrap().contents.as_deref(),
+            Some("Hello & welcome")
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 11);
+        assert_eq!(diagnostics[0].level, log::Level::Warn);
+        assert_eq!(doc.first_element(), Some("event"));
+
+        // no root element, invalid UTF-8
+        let mut diagnostics = Vec::new();
+        let doc = preprocess(
+            "a.xml",
+            b"<text name=\"a\">\xff</text>\n</oops>",
+            &mut diagnostics,
+        );
+        assert_eq!(diagnostics.len(), 2);
+        let text: XmlText = doc.parse(&mut diagnostics);
+        assert_eq!(text.text[0].value, "\u{fffd}");
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(doc.first_element(), Some("text"));
+
+        let doc = preprocess("b.xml", b"<!-- empty --><FTL></FTL>", &mut diagnostics);
+        assert_eq!(doc.first_element(), None);
+        let doc = preprocess("c.xml", b"<achievement/>", &mut diagnostics);
+        assert_eq!(doc.first_element(), Some("achievement"));
+    }
+}
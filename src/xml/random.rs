@@ -0,0 +1,231 @@
+//! A [`Deserializer`] that makes up random values, used for generating arbitrary game data in
+//! round trip tests without writing a generator for every type in the schema.
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{
+    de::{
+        value::{BorrowedStrDeserializer, Error},
+        DeserializeOwned, DeserializeSeed, EnumAccess, Error as _, MapAccess, SeqAccess,
+        VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserializer,
+};
+
+/// Options and lists stop being generated past this depth so recursive types stay finite
+const MAX_DEPTH: u32 = 6;
+
+struct Gen {
+    rng: StdRng,
+    depth: u32,
+}
+
+/// A random value of type `T`, fully determined by the seed.
+pub fn value<T: DeserializeOwned>(seed: u64) -> T {
+    let mut gen = Gen {
+        rng: StdRng::seed_from_u64(seed),
+        depth: 0,
+    };
+    T::deserialize(&mut gen).unwrap()
+}
+
+impl Gen {
+    fn nested(&mut self) -> bool {
+        self.depth < MAX_DEPTH && self.rng.gen_bool(0.5)
+    }
+    /// Text that survives a trip through XML: non-empty, no leading or trailing whitespace (the
+    /// deserializer trims it), and including characters that have to be escaped.
+    fn string(&mut self) -> String {
+        const CHARS: &[u8] = b"aAzZ09_-.,!?&<>\"' ";
+        let len = self.rng.gen_range(1..12);
+        let ret: String = (0..len)
+            .map(|_| *CHARS.choose(&mut self.rng).unwrap() as char)
+            .collect();
+        match ret.trim() {
+            "" => "x".to_owned(),
+            x => x.to_owned(),
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for &mut Gen {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::custom("can't generate a value of an unknown type"))
+    }
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bool(self.rng.gen())
+    }
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u8(self.rng.gen())
+    }
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u16(self.rng.gen())
+    }
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u32(self.rng.gen())
+    }
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_u64(self.rng.gen())
+    }
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i8(self.rng.gen())
+    }
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i16(self.rng.gen())
+    }
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i32(self.rng.gen())
+    }
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_i64(self.rng.gen())
+    }
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f32(self.rng.gen_range(-1000.0..1000.0))
+    }
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_f64(self.rng.gen_range(-1000.0..1000.0))
+    }
+    // borrowed strings are only used by `case_insensitive_de`
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(if self.rng.gen() { "true" } else { "false" })
+    }
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.string())
+    }
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.nested() {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let len = if self.depth < MAX_DEPTH {
+            self.rng.gen_range(0..4)
+        } else {
+            0
+        };
+        self.depth += 1;
+        let ret = visitor.visit_seq(Seq { gen: self, len });
+        self.depth -= 1;
+        ret
+    }
+    // only reached through `#[serde(flatten)]`, where the field names aren't known
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(Struct {
+            gen: self,
+            fields: &[],
+        })
+    }
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.depth += 1;
+        let ret = visitor.visit_map(Struct { gen: self, fields });
+        self.depth -= 1;
+        ret
+    }
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let variant = variants.choose(&mut self.rng).unwrap();
+        visitor.visit_enum(Enum { gen: self, variant })
+    }
+
+    forward_to_deserialize_any! {
+        char bytes byte_buf unit_struct tuple tuple_struct identifier ignored_any
+    }
+}
+
+struct Seq<'a> {
+    gen: &'a mut Gen,
+    len: usize,
+}
+
+impl<'de> SeqAccess<'de> for Seq<'_> {
+    type Error = Error;
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.gen).map(Some)
+    }
+}
+
+struct Struct<'a> {
+    gen: &'a mut Gen,
+    fields: &'static [&'static str],
+}
+
+impl<'de> MapAccess<'de> for Struct<'_> {
+    type Error = Error;
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some((field, rest)) = self.fields.split_first() else {
+            return Ok(None);
+        };
+        self.fields = rest;
+        seed.deserialize(BorrowedStrDeserializer::new(field))
+            .map(Some)
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.gen)
+    }
+}
+
+struct Enum<'a> {
+    gen: &'a mut Gen,
+    variant: &'static str,
+}
+
+impl<'de> EnumAccess<'de> for Enum<'_> {
+    type Error = Error;
+    type Variant = Self;
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant = seed.deserialize(BorrowedStrDeserializer::new(self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Enum<'_> {
+    type Error = Error;
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.gen)
+    }
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error::custom("tuple variants aren't supported"))
+    }
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.gen.deserialize_struct("", fields, visitor)
+    }
+}